@@ -0,0 +1,479 @@
+use crate::context::Context;
+use crate::error::Error;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use swc_common::Span;
+use swc_ecma_ast::{Expr, Lit, ObjectLit, Prop, PropOrSpread};
+
+/// The value of a call of a built-in function that is commonly used to embed data, with a context
+/// for it. These are `JSON.parse(text)`, `Object.freeze(object)`, and
+/// `Object.assign(target, ...sources)`, whose arguments must evaluate to literals.
+///
+/// Expressions of the call are converted with `value`, which either borrows or clones them.
+/// Returns `None` if the expression is not such a call.
+pub fn call<'s, 'de, F: Fn(&'s Expr) -> Cow<'de, Expr>>(
+    expr: &'s Expr,
+    context: &Context<'de>,
+    value: &F,
+) -> Option<Result<(Cow<'de, Expr>, Context<'de>), Error>> {
+    let call = expr.unwrap_parens().as_call()?;
+    let (object, name) = crate::member_variant(call.callee.as_expr()?.as_member()?)?;
+    let args = call
+        .args
+        .iter()
+        .map(|arg| arg.spread.is_none().then_some(&*arg.expr))
+        .collect::<Option<Vec<_>>>()?;
+
+    match (object.as_str(), name, args.as_slice()) {
+        ("Object", "freeze", [object]) => Some(Ok((value(object), context.clone()))),
+        ("Object", "assign", [_, ..]) => Some(
+            assign(call.span, args.into_iter().map(value), context)
+                .map(|object| (Cow::Owned(object), context.clone())),
+        ),
+        #[cfg(feature = "json")]
+        ("JSON", "parse", [text]) => Some(parse_json(value(text), context)),
+        _ => None,
+    }
+}
+
+/// Merge objects as `Object.assign` does, where a later property replaces the value of an earlier
+/// one with the same key but keeps its position. Sources that are `null` or `undefined` are
+/// skipped.
+fn assign<'de, I: Iterator<Item = Cow<'de, Expr>>>(
+    span: Span,
+    objects: I,
+    context: &Context<'de>,
+) -> Result<Expr, Error> {
+    let mut props: Vec<PropOrSpread> = vec![];
+    let mut positions = HashMap::new();
+
+    for (index, object) in objects.enumerate() {
+        // Each source is evaluated in its own context, such as the scope of a function that
+        // returns it, so its properties are inlined before they are merged.
+        let object = context.clone().inline(object)?;
+
+        let source = match &*object {
+            Expr::Object(ObjectLit { props, .. }) => props,
+            Expr::Lit(Lit::Null(_)) if index > 0 => continue,
+            Expr::Ident(ident) if index > 0 && ident.sym == "undefined" => continue,
+            other => return Err(Error::UnexpectedExpr(other.clone())),
+        };
+
+        for prop_or_spread in source {
+            let key = match prop_or_spread {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::KeyValue(kvp) => crate::prop_name_to_str(&kvp.key),
+                    Prop::Shorthand(ident) => Some(ident.sym.as_str()),
+                    _ => None,
+                }
+                .ok_or_else(|| Error::UnexpectedProp(prop.clone()))?,
+                PropOrSpread::Spread(spread) => {
+                    return Err(Error::UnexpectedSpread(spread.clone()));
+                }
+            };
+
+            if let Some(&position) = positions.get(key) {
+                props[position] = prop_or_spread.clone();
+            } else {
+                positions.insert(key.to_string(), props.len());
+                props.push(prop_or_spread.clone());
+            }
+        }
+    }
+
+    Ok(Expr::Object(ObjectLit { span, props }))
+}
+
+/// Parse the text passed to `JSON.parse`, which must be JSON. The value has no spans, since they
+/// would refer to the text rather than to the source.
+#[cfg(feature = "json")]
+fn parse_json<'de>(
+    text: Cow<'de, Expr>,
+    context: &Context<'de>,
+) -> Result<(Cow<'de, Expr>, Context<'de>), Error> {
+    let (text, text_context) = context.clone().evaluate(text)?;
+    let text = string(&text, &text_context)?;
+
+    context.options().check_input(&text)?;
+
+    let value = serde_json::from_str::<crate::Value>(&text).map_err(serde::de::Error::custom)?;
+
+    Ok((Cow::Owned(value.to_expr()), context.detached()))
+}
+
+/// The result of a call of a global function that decodes a string.
+pub enum Decoded {
+    String(String),
+    /// The bytes returned by `atob`, which JavaScript represents as a string with a character
+    /// for each byte.
+    Binary(Vec<u8>),
+}
+
+impl Decoded {
+    pub fn into_string(self) -> String {
+        match self {
+            Self::String(string) => string,
+            Self::Binary(bytes) => bytes.into_iter().map(char::from).collect(),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Self::String(string) => string.into_bytes(),
+            Self::Binary(bytes) => bytes,
+        }
+    }
+}
+
+/// Evaluate a call of `atob`, `decodeURIComponent`, `unescape`, or `String.fromCharCode`, if
+/// decoding strings is enabled.
+///
+/// Returns `None` if the expression is not such a call.
+pub fn decode(expr: &Expr, context: &Context<'_>) -> Option<Result<Decoded, Error>> {
+    if !context.options().decode_strings {
+        return None;
+    }
+
+    decoded(expr, context).map(|decoded| {
+        let decoded = decoded?;
+
+        context.consume_string(match &decoded {
+            Decoded::String(string) => string.len(),
+            Decoded::Binary(bytes) => bytes.len(),
+        })?;
+
+        Ok(decoded)
+    })
+}
+
+fn decoded(expr: &Expr, context: &Context<'_>) -> Option<Result<Decoded, Error>> {
+    let call = expr.unwrap_parens().as_call()?;
+    let name = match &**call.callee.as_expr()? {
+        Expr::Ident(ident) => ident.sym.to_string(),
+        Expr::Member(member) => {
+            let (object, name) = crate::member_variant(member)?;
+
+            format!("{object}.{name}")
+        }
+        _ => return None,
+    };
+    let args = call
+        .args
+        .iter()
+        .map(|arg| arg.spread.is_none().then_some(&*arg.expr))
+        .collect::<Option<Vec<_>>>()?;
+
+    let invalid = || Error::InvalidEncodedString(expr.clone());
+
+    Some(match (name.as_str(), args.as_slice()) {
+        ("atob", [input]) => string_argument(input, context)
+            .and_then(|input| atob(&input).map(Decoded::Binary).ok_or_else(invalid)),
+        ("decodeURIComponent", [input]) => string_argument(input, context).and_then(|input| {
+            decode_uri_component(&input)
+                .map(Decoded::String)
+                .ok_or_else(invalid)
+        }),
+        ("unescape", [input]) => string_argument(input, context)
+            .and_then(|input| unescape(&input).map(Decoded::String).ok_or_else(invalid)),
+        ("String.fromCharCode", codes) => codes
+            .iter()
+            .map(|code| char_code(code, context))
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|units| {
+                String::from_utf16(&units)
+                    .map(Decoded::String)
+                    .map_err(|_| invalid())
+            }),
+        _ => return None,
+    })
+}
+
+/// The string that an argument evaluates to, which may itself be decoded.
+fn string_argument(expr: &Expr, context: &Context<'_>) -> Result<String, Error> {
+    let (value, context) = context.clone().evaluate(Cow::Owned(expr.clone()))?;
+
+    string(&value, &context)
+}
+
+/// The value of a string literal, or of a call that decodes a string.
+fn string(expr: &Expr, context: &Context<'_>) -> Result<String, Error> {
+    match expr {
+        Expr::Lit(Lit::Str(str)) => Ok(str.value.to_string()),
+        other if context.options().decode_strings => decoded(other, context)
+            .unwrap_or_else(|| Err(Error::UnexpectedExpr(other.clone())))
+            .map(Decoded::into_string),
+        other => Err(Error::UnexpectedExpr(other.clone())),
+    }
+}
+
+/// A UTF-16 code unit given to `String.fromCharCode`, which converts it to a number modulo 2^16.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn char_code(expr: &Expr, context: &Context<'_>) -> Result<u16, Error> {
+    let (value, _) = context.clone().evaluate(Cow::Owned(expr.clone()))?;
+
+    let code = match &*value {
+        Expr::Lit(Lit::Str(str)) => crate::number::string_to_number(&str.value),
+        other => {
+            crate::number::expr_to_f64(other).ok_or_else(|| Error::UnexpectedExpr(other.clone()))?
+        }
+    };
+
+    Ok(if code.is_finite() {
+        code.trunc().rem_euclid(65536.0) as u16
+    } else {
+        0
+    })
+}
+
+/// Decode base64 as `atob` does, ignoring whitespace and allowing the padding to be omitted.
+fn atob(input: &str) -> Option<Vec<u8>> {
+    let mut input = input
+        .bytes()
+        .filter(|byte| !matches!(byte, b'\t' | b'\n' | b'\x0c' | b'\r' | b' '))
+        .collect::<Vec<_>>();
+
+    if input.len() % 4 == 0 {
+        for _ in 0..2 {
+            if input.last() == Some(&b'=') {
+                input.pop();
+            }
+        }
+    }
+
+    if input.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0_u32;
+    let mut bits = 0;
+
+    for byte in input {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits).to_le_bytes()[0]);
+        }
+    }
+
+    Some(bytes)
+}
+
+/// Decode percent-encoded UTF-8 as `decodeURIComponent` does, failing if it is not valid.
+fn decode_uri_component(input: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut rest = input.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+/// Decode `%XX` and `%uXXXX` escapes as `unescape` does, failing if the result has unpaired
+/// surrogates.
+fn unescape(input: &str) -> Option<String> {
+    let units = input.encode_utf16().collect::<Vec<_>>();
+    let hex = |units: &[u16]| {
+        String::from_utf16(units)
+            .ok()
+            .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .and_then(|hex| u16::from_str_radix(&hex, 16).ok())
+    };
+
+    let mut result = Vec::with_capacity(units.len());
+    let mut index = 0;
+
+    while index < units.len() {
+        let escape = if units[index] == u16::from(b'%') {
+            if units.get(index + 1) == Some(&u16::from(b'u')) {
+                units
+                    .get(index + 2..index + 6)
+                    .and_then(hex)
+                    .map(|unit| (unit, 6))
+            } else {
+                units
+                    .get(index + 1..index + 3)
+                    .and_then(hex)
+                    .map(|unit| (unit, 3))
+            }
+        } else {
+            None
+        };
+
+        let (unit, length) = escape.unwrap_or((units[index], 1));
+
+        result.push(unit);
+        index += length;
+    }
+
+    String::from_utf16(&result).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::{Script, Value, js};
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        name: String,
+        tags: Vec<String>,
+        limit: Option<u32>,
+    }
+
+    #[test]
+    fn builtin_calls() -> Result<(), Error> {
+        assert_eq!(
+            crate::from_str::<Config>(r#"JSON.parse('{"name":"a","tags":["b"],"limit":null}')"#)?,
+            Config {
+                name: "a".to_string(),
+                tags: vec!["b".to_string()],
+                limit: None,
+            }
+        );
+        assert_eq!(
+            crate::from_str::<Value>(r#"JSON.parse("{\"a\":\"\\u0041\",\"b\":[1.5,true]}")"#)?,
+            js!({ a: "A", b: [1.5, true] })
+        );
+        assert_eq!(
+            crate::from_str::<Value>("Object.freeze([Object.freeze({ a: 1 })])")?,
+            js!([{ a: 1 }])
+        );
+        assert_eq!(
+            crate::from_str::<Value>("Object.assign({}, { a: 1, b: 2 }, null, { b: 3, c: 4 })")?,
+            js!({ a: 1, b: 3, c: 4 })
+        );
+
+        let script = Script::parse(
+            r#"
+            var base = { name: "base", tags: [] };
+            var config = Object.freeze(Object.assign({}, base, { tags: JSON.parse(tags) }));
+            var tags = '["x", "y"]';
+            "#,
+        )?;
+
+        assert_eq!(
+            script.get::<Config>("config")?,
+            Config {
+                name: "base".to_string(),
+                tags: vec!["x".to_string(), "y".to_string()],
+                limit: None,
+            }
+        );
+
+        assert!(crate::from_str::<Value>("Object.assign(1, {})").is_err());
+        assert!(crate::from_str::<Value>("Object.assign({}, { ...a })").is_err());
+        assert!(crate::from_str::<Value>("JSON.parse(1)").is_err());
+        assert!(crate::from_str::<Value>("JSON.parse('{')").is_err());
+        assert!(crate::from_str::<Value>(r#"JSON.parse("{a: 1, 'b': undefined}")"#).is_err());
+        assert!(crate::from_str::<Value>("JSON.parse('[1,]')").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn assign_sources_in_their_own_scope() -> Result<(), Error> {
+        let script = Script::parse(
+            "var a = 7; var v = Object.assign({}, (function(a){ return {x: a}; })(5));",
+        )?;
+
+        assert_eq!(script.get::<Value>("v")?, js!({ x: 5 }));
+
+        let script = Script::parse(
+            "var a = 7; var v = Object.assign({a}, (function(a){ return {b: [a, {a}]}; })(5));",
+        )?;
+
+        assert_eq!(script.get::<Value>("v")?, js!({ a: 7, b: [5, { a: 5 }] }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_parse_has_no_location() -> Result<(), Error> {
+        let spanned = crate::from_str::<crate::Spanned<Value>>(r#"JSON.parse('{"x": 1}')"#)?;
+
+        assert_eq!(spanned.span(), 0..0);
+        assert_eq!(spanned.start(), None);
+        assert_eq!(spanned.into_inner(), js!({ x: 1 }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn decode_strings() -> Result<(), Error> {
+        let options = crate::Options::new().decode_strings(true);
+        let string = |source: &str| options.from_str::<String>(source);
+        let bytes = |source: &str| {
+            options
+                .from_str::<serde_bytes::ByteBuf>(source)
+                .map(serde_bytes::ByteBuf::into_vec)
+        };
+
+        assert_eq!(string(r#"atob("aGVsbG8=")"#)?, "hello");
+        assert_eq!(string(r#"atob(" aGVs\nbG8 ")"#)?, "hello");
+        assert_eq!(string(r#"atob("/w")"#)?, "\u{ff}");
+        assert_eq!(bytes(r#"atob("/w==")"#)?, vec![0xff]);
+        assert_eq!(
+            string(r#"decodeURIComponent("caf%C3%A9%20au%20lait")"#)?,
+            "café au lait"
+        );
+        assert_eq!(bytes(r#"decodeURIComponent("%C3%A9")"#)?, "é".as_bytes());
+        assert_eq!(string(r#"unescape("%u00e9%41%zz")"#)?, "éA%zz");
+        assert_eq!(string("String.fromCharCode(72, 105, 65601)")?, "HiA");
+        assert_eq!(string(r#"decodeURIComponent(unescape("%2541"))"#)?, "A");
+        assert_eq!(
+            options.from_str::<Value>(r#"[atob("aGk="), JSON.parse(atob("WzFd"))]"#)?,
+            js!(["hi", [1]])
+        );
+        assert_eq!(
+            options
+                .parse_script(r#"var s = "aGk="; var t = atob(s);"#)?
+                .get::<String>("t")?,
+            "hi"
+        );
+
+        assert!(matches!(
+            string(r#"atob("a")"#),
+            Err(Error::InvalidEncodedString(_))
+        ));
+        assert!(matches!(
+            string(r#"decodeURIComponent("%C3")"#),
+            Err(Error::InvalidEncodedString(_))
+        ));
+        assert!(matches!(
+            string("String.fromCharCode(55296)"),
+            Err(Error::InvalidEncodedString(_))
+        ));
+        assert!(matches!(
+            string(r#"btoa("hi")"#),
+            Err(Error::UnexpectedExpr(_))
+        ));
+        assert!(matches!(
+            crate::from_str::<String>(r#"atob("aGk=")"#),
+            Err(Error::UnexpectedExpr(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+#![allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+use crate::context::Context;
+use crate::error::Error;
+use swc_common::Spanned as _;
+use swc_ecma_ast::{ArrayLit, Expr, ExprOrSpread, Lit, UnaryExpr, UnaryOp};
+
+pub const TYPED_ARRAYS: &[&str] = &[
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "BigInt64Array",
+    "BigUint64Array",
+];
+
+/// Read the bytes of an array of small integers, a typed array, or an `ArrayBuffer`.
+///
+/// The contents of typed arrays with elements wider than a byte are given in little-endian order,
+/// which matches the underlying buffer on all common platforms. Returns `None` if the expression
+/// is none of these.
+pub fn from_expr(expr: &Expr, context: &Context<'_>) -> Option<Result<Vec<u8>, Error>> {
+    match expr {
+        Expr::Array(ArrayLit { elems, .. }) => Some(
+            context
+                .enter(elems.len())
+                .and_then(|()| elems_to_bytes(elems, context, small_integer)),
+        ),
+        Expr::New(new) => {
+            let name = new.callee.as_ident()?.sym.as_str();
+            let args = new.args.as_deref().unwrap_or_default();
+
+            match (name, args) {
+                ("ArrayBuffer", [ExprOrSpread { spread: None, expr }]) => {
+                    Some(array_buffer(expr, context))
+                }
+                (name, []) if TYPED_ARRAYS.contains(&name) => Some(Ok(vec![])),
+                (name, [ExprOrSpread { spread: None, expr }]) if TYPED_ARRAYS.contains(&name) => {
+                    Some(match &**expr {
+                        Expr::Array(ArrayLit { elems, .. }) => context
+                            .enter(elems.len())
+                            .and_then(|()| typed_array(name, elems, context)),
+                        other => Err(Error::UnexpectedExpr(other.clone())),
+                    })
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// A new `ArrayBuffer` of the given length, which is filled with zeros.
+fn array_buffer(length: &Expr, context: &Context<'_>) -> Result<Vec<u8>, Error> {
+    let length = crate::number::expr_to_f64(length)
+        .filter(|length| {
+            length.fract() == 0.0 && *length >= 0.0 && *length <= 9_007_199_254_740_991.0
+        })
+        .ok_or_else(|| Error::UnexpectedExpr(length.clone()))? as usize;
+
+    context.enter(length)?;
+
+    Ok(vec![0; length])
+}
+
+fn typed_array(
+    name: &str,
+    elems: &[Option<ExprOrSpread>],
+    context: &Context<'_>,
+) -> Result<Vec<u8>, Error> {
+    match name {
+        "Int8Array" | "Uint8Array" => elems_to_bytes(elems, context, |expr| {
+            modulo(expr, 8).map(|value| [value as u8])
+        }),
+        "Uint8ClampedArray" => elems_to_bytes(elems, context, |expr| {
+            crate::number::expr_to_f64(expr).map(|value| {
+                [if value.is_nan() {
+                    0
+                } else {
+                    value.clamp(0.0, 255.0).round_ties_even() as u8
+                }]
+            })
+        }),
+        "Int16Array" | "Uint16Array" => elems_to_bytes(elems, context, |expr| {
+            modulo(expr, 16).map(|value| (value as u16).to_le_bytes())
+        }),
+        "Int32Array" | "Uint32Array" => elems_to_bytes(elems, context, |expr| {
+            modulo(expr, 32).map(|value| (value as u32).to_le_bytes())
+        }),
+        "Float32Array" => elems_to_bytes(elems, context, |expr| {
+            crate::number::expr_to_f64(expr).map(|value| (value as f32).to_le_bytes())
+        }),
+        "Float64Array" => elems_to_bytes(elems, context, |expr| {
+            crate::number::expr_to_f64(expr).map(f64::to_le_bytes)
+        }),
+        _ => elems_to_bytes(elems, context, big_int_bytes),
+    }
+}
+
+/// Convert each element, failing on holes, spreads, and elements that cannot be converted.
+fn elems_to_bytes<const N: usize, F: Fn(&Expr) -> Option<[u8; N]>>(
+    elems: &[Option<ExprOrSpread>],
+    context: &Context<'_>,
+    f: F,
+) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::with_capacity(elems.len() * N);
+
+    for (index, elem) in elems.iter().enumerate() {
+        match elem {
+            Some(ExprOrSpread { spread: None, expr }) => {
+                if let Some(value) = f(expr) {
+                    bytes.extend_from_slice(&value);
+                } else {
+                    let context = context.child(|| crate::path::Segment::Index(index));
+                    let error = match &**expr {
+                        Expr::Lit(lit) => Error::unexpected_lit(lit, "byte"),
+                        other => Error::UnexpectedExpr(other.clone()),
+                    };
+
+                    return Err(context.locate(error, expr.span()));
+                }
+            }
+            other => return Err(Error::InvalidArrayElement(other.clone())),
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// An element of a plain array used as bytes, which must be an integer from 0 to 255.
+fn small_integer(expr: &Expr) -> Option<[u8; 1]> {
+    match expr {
+        Expr::Lit(Lit::Num(number)) => crate::number::number_to_u8(number).map(|value| [value]),
+        _ => None,
+    }
+}
+
+/// Convert a number to an integer modulo `2^bits`, as typed arrays do when storing elements.
+fn modulo(expr: &Expr, bits: i32) -> Option<u64> {
+    crate::number::expr_to_f64(expr).map(|value| {
+        if value.is_finite() {
+            value.trunc().rem_euclid(2_f64.powi(bits)) as u64
+        } else {
+            0
+        }
+    })
+}
+
+/// Convert a big integer to a 64-bit integer in two's complement, as `BigInt64Array` does.
+fn big_int_bytes(expr: &Expr) -> Option<[u8; 8]> {
+    let value = match expr {
+        Expr::Lit(Lit::BigInt(big_int)) => (*big_int.value).clone(),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) => match &**arg {
+            Expr::Lit(Lit::BigInt(big_int)) => -(*big_int.value).clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let mut bytes = value.to_signed_bytes_le();
+    let fill = if bytes.last().is_some_and(|byte| byte & 0x80 != 0) {
+        0xff
+    } else {
+        0
+    };
+
+    bytes.resize(8, fill);
+
+    let mut result = [0; 8];
+    result.copy_from_slice(&bytes[..8]);
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn typed_arrays() -> Result<(), crate::error::Error> {
+        let bytes = |source: &str| -> Result<Vec<u8>, crate::error::Error> {
+            crate::from_str::<serde_bytes::ByteBuf>(source).map(serde_bytes::ByteBuf::into_vec)
+        };
+
+        assert_eq!(bytes("[0, 1, 255]")?, vec![0, 1, 255]);
+        assert_eq!(bytes(r#""abc""#)?, b"abc".to_vec());
+        assert_eq!(
+            bytes("new Uint8Array([1, 2, 300, -1])")?,
+            vec![1, 2, 44, 255]
+        );
+        assert_eq!(
+            bytes("new Uint8ClampedArray([300, -1, 1.5, 2.5])")?,
+            vec![255, 0, 2, 2]
+        );
+        assert_eq!(bytes("new Int16Array([-2, 258])")?, vec![254, 255, 2, 1]);
+        assert_eq!(
+            bytes("new Float32Array([1])")?,
+            1_f32.to_le_bytes().to_vec()
+        );
+        assert_eq!(
+            bytes("new BigInt64Array([-2n, 1n])")?,
+            [(-2_i64).to_le_bytes(), 1_i64.to_le_bytes()].concat()
+        );
+        assert_eq!(bytes("new BigUint64Array([-1n])")?, vec![255; 8]);
+        assert_eq!(bytes("new ArrayBuffer(3)")?, vec![0; 3]);
+        assert_eq!(bytes("new Uint8Array()")?, Vec::<u8>::new());
+
+        assert!(bytes("[1, 256]").is_err());
+        assert!(bytes("[1, , 2]").is_err());
+        assert!(
+            crate::Options::new()
+                .max_length(2)
+                .from_str::<serde_bytes::ByteBuf>("new ArrayBuffer(3)")
+                .is_err()
+        );
+
+        assert_eq!(
+            crate::from_str::<Vec<u16>>("new Uint16Array([1, 65535])")?,
+            vec![1, 65535]
+        );
+        assert_eq!(
+            crate::from_str::<Vec<f32>>("new Float32Array([0.5, 2])")?,
+            vec![0.5, 2.0]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,602 @@
+use crate::error::Error;
+use crate::options::{Limit, Options};
+use crate::path::{Node, Path, Segment};
+use crate::script::{Lookup, Scope};
+use crate::spanned::LineColumn;
+use crate::value::Value;
+use crate::warning::{Warning, WarningKind};
+use std::borrow::Cow;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::rc::Rc;
+use swc_common::{BytePos, Span};
+use swc_ecma_ast::{CallExpr, Callee, Expr, KeyValueProp, NewExpr, Prop, PropName, PropOrSpread};
+
+/// State shared by every deserializer created for a single top-level deserialization.
+#[derive(Clone, Debug, Default)]
+pub struct Context<'de> {
+    shared: Rc<Shared>,
+    path: Option<Rc<Node>>,
+    depth: usize,
+    /// How deeply the operators being folded are nested.
+    fold_depth: usize,
+    /// The source text that spans refer to, if it is available.
+    source: Option<&'de str>,
+    /// The bindings that identifiers refer to, if any.
+    scope: Option<Rc<Scope<'de>>>,
+    /// The bindings being resolved, used to detect cycles.
+    resolving: Option<Rc<Resolving<'de>>>,
+}
+
+#[derive(Debug)]
+struct Resolving<'de> {
+    name: String,
+    /// The scope in which the name is bound, since a function may shadow an enclosing binding.
+    scope: Rc<Scope<'de>>,
+    parent: Option<Rc<Self>>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    options: Options,
+    /// The total number of string bytes deserialized so far.
+    string_bytes: Cell<usize>,
+    /// The total number of operators folded so far.
+    fold_steps: Cell<usize>,
+    /// The byte offsets at which lines start in the source, computed when first needed.
+    line_starts: OnceCell<Vec<usize>>,
+    /// When present, recoverable errors are collected here instead of being returned.
+    errors: Option<RefCell<Vec<Error>>>,
+    /// When present, lossy conversions are reported here.
+    warnings: Option<RefCell<Vec<Warning>>>,
+    /// When present, the paths of values that were ignored by the target type are recorded here.
+    ignored: Option<RefCell<Vec<Path>>>,
+}
+
+impl<'de> Context<'de> {
+    fn new(shared: Shared) -> Self {
+        Self {
+            shared: Rc::new(shared),
+            path: None,
+            depth: 0,
+            fold_depth: 0,
+            source: None,
+            scope: None,
+            resolving: None,
+        }
+    }
+
+    /// Spans are interpreted as byte offsets into the source, starting at zero.
+    pub fn with_source(self, source: &'de str) -> Self {
+        Self {
+            source: Some(source),
+            ..self
+        }
+    }
+
+    /// Identifiers are resolved to the bindings in the scope.
+    pub fn with_scope(self, scope: Scope<'de>) -> Self {
+        Self {
+            scope: Some(Rc::new(scope)),
+            ..self
+        }
+    }
+
+    /// The value bound to a name in the script being deserialized, with a context that records
+    /// that the binding is being resolved.
+    ///
+    /// A name that is not bound in the script may be one of the globals given in the options, or
+    /// a variable of `process.env`, which is `undefined` if the environment was given without it.
+    ///
+    /// Returns `None` if the name is not bound.
+    pub fn resolve(&self, name: &str) -> Option<Result<(Cow<'de, Expr>, Self), Error>> {
+        let Some(Lookup {
+            scope,
+            value,
+            value_scope,
+        }) = self.scope.as_ref().and_then(|scope| scope.lookup(name))
+        else {
+            return self.global(name);
+        };
+        let mut resolving = self.resolving.as_deref();
+
+        while let Some(node) = resolving {
+            if node.name == name && Rc::ptr_eq(&node.scope, &scope) {
+                return Some(Err(Error::CyclicBinding(name.to_string())));
+            }
+
+            resolving = node.parent.as_deref();
+        }
+
+        Some(value.map(|value| {
+            let context = Self {
+                scope: value_scope,
+                resolving: Some(Rc::new(Resolving {
+                    name: name.to_string(),
+                    scope,
+                    parent: self.resolving.clone(),
+                })),
+                ..self.clone()
+            };
+
+            (value, context)
+        }))
+    }
+
+    /// The value of a global given in the options, which does not refer to any bindings.
+    fn global(&self, name: &str) -> Option<Result<(Cow<'de, Expr>, Self), Error>> {
+        let value = self
+            .options()
+            .globals
+            .get(name)
+            .map(Value::to_expr)
+            .or_else(|| {
+                (self.options().env && name.starts_with("process.env."))
+                    .then(|| Value::Undefined.to_expr())
+            })?;
+
+        Some(Ok((
+            Cow::Owned(value),
+            Self {
+                scope: None,
+                ..self.clone()
+            },
+        )))
+    }
+
+    /// The value of a call that can be evaluated, with a context for it. This is either an
+    /// immediately invoked function, whose parameters are bound in the context, or a built-in
+    /// function such as `JSON.parse`.
+    ///
+    /// Expressions of the call are converted with `value`, which either borrows or clones them.
+    /// Returns `None` if the expression is not a call that can be evaluated.
+    pub fn call<'s, F: Fn(&'s Expr) -> Cow<'de, Expr>>(
+        &self,
+        expr: &'s Expr,
+        value: F,
+    ) -> Option<Result<(Cow<'de, Expr>, Self), Error>> {
+        if let Some((returned, scope)) = Scope::invoke(expr, self.scope.clone(), &value) {
+            let context = Self {
+                scope: Some(Rc::new(scope)),
+                ..self.clone()
+            };
+
+            return Some(Ok((returned, context)));
+        }
+
+        crate::builtin::call(expr, self, &value)
+    }
+
+    /// The value of an expression after resolving identifiers, evaluating calls, reading
+    /// properties of literals, and choosing the operands of conditional and logical operators,
+    /// with the context in which it is evaluated. Other operators are folded if that is enabled.
+    ///
+    /// Only the expression itself is evaluated, not the elements of an array or the values of an
+    /// object, so that those can still be borrowed if they are not evaluated.
+    pub fn evaluate(mut self, mut expr: Cow<'de, Expr>) -> Result<(Cow<'de, Expr>, Self), Error> {
+        loop {
+            let evaluated = if let Expr::Ident(ident) = &*expr {
+                self.resolve(ident.sym.as_str())
+            } else {
+                match &expr {
+                    Cow::Borrowed(borrowed) => self
+                        .call(borrowed, Cow::Borrowed)
+                        .or_else(|| crate::script::member(borrowed, &self, &Cow::Borrowed))
+                        .or_else(|| crate::script::choose(borrowed, &self, &Cow::Borrowed)),
+                    Cow::Owned(owned) => {
+                        let clone = |expr: &Expr| Cow::Owned(expr.clone());
+
+                        self.call(owned, clone)
+                            .or_else(|| crate::script::member(owned, &self, &clone))
+                            .or_else(|| crate::script::choose(owned, &self, &clone))
+                    }
+                }
+            };
+
+            match evaluated {
+                Some(evaluated) => (expr, self) = evaluated?,
+                None => return Ok((expr, self)),
+            }
+        }
+    }
+
+    /// The value of an expression as `evaluate` gives it, with the elements of arrays, the values
+    /// of objects, and the arguments of calls in it also evaluated, so that it no longer depends on this context.
+    /// Expressions that cannot be evaluated, such as functions, are kept as they are.
+    ///
+    /// A borrowed expression is only cloned if something in it was evaluated.
+    pub fn inline(self, expr: Cow<'de, Expr>) -> Result<Cow<'de, Expr>, Error> {
+        let (expr, context) = self.evaluate(expr)?;
+
+        match expr {
+            Cow::Borrowed(borrowed) => {
+                let mut values = vec![];
+                let mut changed = false;
+
+                for child in children(borrowed) {
+                    let value = context.clone().inline(child.clone())?;
+
+                    changed |= match (&value, &child) {
+                        (Cow::Borrowed(value), Cow::Borrowed(child)) => {
+                            !std::ptr::eq(*value, *child)
+                        }
+                        _ => *value != *child,
+                    };
+                    values.push(value);
+                }
+
+                if !changed {
+                    return Ok(Cow::Borrowed(borrowed));
+                }
+
+                let mut owned = borrowed.clone();
+
+                for (child, value) in children_mut(&mut owned).into_iter().zip(values) {
+                    *child = value.into_owned();
+                }
+
+                Ok(Cow::Owned(owned))
+            }
+            Cow::Owned(mut owned) => {
+                for child in children_mut(&mut owned) {
+                    *child = context
+                        .clone()
+                        .inline(Cow::Owned(std::mem::take(child)))?
+                        .into_owned();
+                }
+
+                Ok(Cow::Owned(owned))
+            }
+        }
+    }
+
+    /// A context for a value that was parsed from a string, whose spans do not refer to the source
+    /// and whose identifiers do not refer to bindings.
+    #[cfg(feature = "json")]
+    pub fn detached(&self) -> Self {
+        Self {
+            source: None,
+            scope: None,
+            ..self.clone()
+        }
+    }
+
+    /// A context for the operands of an operator that is being folded, which counts the operator
+    /// against the limit on folding steps.
+    pub fn fold_step(&self) -> Result<Self, Error> {
+        let steps = self.shared.fold_steps.get() + 1;
+        self.shared.fold_steps.set(steps);
+
+        if let Some(limit) = self.options().limits.max_fold_steps
+            && steps > limit
+        {
+            Err(Error::LimitExceeded(Limit::FoldSteps(limit)))
+        } else if self.fold_depth >= crate::fold::MAX_DEPTH {
+            Err(Error::LimitExceeded(Limit::Depth(crate::fold::MAX_DEPTH)))
+        } else {
+            Ok(Self {
+                fold_depth: self.fold_depth + 1,
+                ..self.clone()
+            })
+        }
+    }
+
+    /// The source text of a span, if the source is available and the span is within it.
+    pub fn source_text(&self, span: Span) -> Option<&'de str> {
+        self.source
+            .and_then(|source| source.get(span.lo.0 as usize..span.hi.0 as usize))
+    }
+
+    /// The source text of a string literal or identifier with the given value, if the source is
+    /// available and spells the value without escapes, so that the value can be borrowed from it
+    /// even when the expression is owned.
+    pub fn borrow_source(&self, value: &str, span: Span) -> Option<&'de str> {
+        let text = self.source_text(span)?;
+        let unquoted = text
+            .strip_prefix(['"', '\''])
+            .and_then(|text| text.strip_suffix(['"', '\'']))
+            .unwrap_or(text);
+
+        (unquoted == value).then_some(unquoted)
+    }
+
+    pub fn line_column(&self, position: BytePos) -> Option<LineColumn> {
+        let source = self.source?;
+        let offset = position.0 as usize;
+        let prefix = source.get(..offset)?;
+
+        let line_starts = self.shared.line_starts.get_or_init(|| {
+            std::iter::once(0)
+                .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+                .collect()
+        });
+
+        let line = line_starts.partition_point(|start| *start <= offset);
+        let line_start = line_starts[line - 1];
+
+        Some(LineColumn {
+            line,
+            column: prefix[line_start..].chars().count(),
+        })
+    }
+
+    pub fn with_options(options: Options) -> Self {
+        Self::new(Shared {
+            options,
+            ..Shared::default()
+        })
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.shared.options
+    }
+
+    pub fn accumulating(options: Options) -> Self {
+        Self::new(Shared {
+            options,
+            errors: Some(RefCell::new(vec![])),
+            ..Shared::default()
+        })
+    }
+
+    pub fn collecting_warnings(options: Options) -> Self {
+        Self::new(Shared {
+            options,
+            warnings: Some(RefCell::new(vec![])),
+            ..Shared::default()
+        })
+    }
+
+    pub fn collecting_ignored(options: Options) -> Self {
+        Self::new(Shared {
+            options,
+            ignored: Some(RefCell::new(vec![])),
+            ..Shared::default()
+        })
+    }
+
+    fn tracks_paths(&self) -> bool {
+        self.shared.errors.is_some()
+            || self.shared.warnings.is_some()
+            || self.shared.ignored.is_some()
+    }
+
+    /// The path segment is only computed if some feature that needs paths is enabled.
+    pub fn child<F: FnOnce() -> Segment>(&self, segment: F) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            path: if self.tracks_paths() {
+                Some(Node::child(self.path.as_ref(), segment()))
+            } else {
+                None
+            },
+            depth: self.depth + 1,
+            fold_depth: self.fold_depth,
+            source: self.source,
+            scope: self.scope.clone(),
+            resolving: self.resolving.clone(),
+        }
+    }
+
+    /// Check the limits that apply when entering an array or object of the given length.
+    pub fn enter(&self, length: usize) -> Result<(), Error> {
+        let limits = &self.shared.options.limits;
+
+        if let Some(limit) = limits.max_depth
+            && self.depth >= limit
+        {
+            Err(Error::LimitExceeded(Limit::Depth(limit)))
+        } else if let Some(limit) = limits.max_length
+            && length > limit
+        {
+            Err(Error::LimitExceeded(Limit::Length(limit)))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Count string bytes against the limit.
+    pub fn consume_string(&self, length: usize) -> Result<(), Error> {
+        let total = self.shared.string_bytes.get().saturating_add(length);
+        self.shared.string_bytes.set(total);
+
+        match self.shared.options.limits.max_string_bytes {
+            Some(limit) if total > limit => Err(Error::LimitExceeded(Limit::StringBytes(limit))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn path(&self) -> Path {
+        Node::to_path(self.path.as_ref())
+    }
+
+    /// Attach the current path and the given span to an error, if we are accumulating errors
+    /// and it does not already have a location.
+    pub fn locate(&self, error: Error, span: Span) -> Error {
+        if self.shared.errors.is_none() || matches!(error, Error::Located { .. }) {
+            error
+        } else {
+            Error::Located {
+                path: self.path(),
+                span,
+                source: Box::new(error),
+            }
+        }
+    }
+
+    /// Record a recoverable error and continue with a placeholder, if we are accumulating errors.
+    pub fn recover<T, F: FnOnce() -> T>(
+        &self,
+        result: Result<T, Error>,
+        span: Span,
+        placeholder: F,
+    ) -> Result<T, Error> {
+        match (result, &self.shared.errors) {
+            (Ok(value), _) => Ok(value),
+            (Err(error), Some(errors)) => {
+                let error = self.locate(error, span);
+                errors.borrow_mut().push(error);
+
+                Ok(placeholder())
+            }
+            (Err(error), None) => Err(error),
+        }
+    }
+
+    pub fn warn<F: FnOnce() -> WarningKind>(&self, kind: F, span: Span) {
+        if let Some(warnings) = &self.shared.warnings {
+            warnings.borrow_mut().push(Warning {
+                kind: kind(),
+                path: self.path(),
+                span,
+            });
+        }
+    }
+
+    pub fn ignore(&self) {
+        if let Some(ignored) = &self.shared.ignored {
+            ignored.borrow_mut().push(self.path());
+        }
+    }
+
+    pub fn take_ignored(&self) -> Vec<Path> {
+        self.shared
+            .ignored
+            .as_ref()
+            .map(RefCell::take)
+            .unwrap_or_default()
+    }
+
+    pub fn take_warnings(&self) -> Vec<Warning> {
+        self.shared
+            .warnings
+            .as_ref()
+            .map(RefCell::take)
+            .unwrap_or_default()
+    }
+
+    pub fn take_errors(&self) -> Vec<Error> {
+        self.shared
+            .errors
+            .as_ref()
+            .map(RefCell::take)
+            .unwrap_or_default()
+    }
+
+    /// A copy of this context that does not borrow the source or the script, with a copy of the
+    /// scope. The bindings being resolved are forgotten, which only delays finding a cycle.
+    pub fn into_owned(self) -> Context<'static> {
+        Context {
+            shared: self.shared,
+            path: self.path,
+            depth: self.depth,
+            fold_depth: self.fold_depth,
+            source: None,
+            scope: self.scope.map(|scope| Rc::new(scope.to_static())),
+            resolving: None,
+        }
+    }
+
+    /// A context for deserializing later with the given options, which counts against the same
+    /// limits but returns errors instead of collecting them, since nothing would report them.
+    pub fn deferred(&self, options: Options) -> Self {
+        Self {
+            shared: Rc::new(Shared {
+                options,
+                string_bytes: self.shared.string_bytes.clone(),
+                fold_steps: self.shared.fold_steps.clone(),
+                ..Shared::default()
+            }),
+            ..self.clone()
+        }
+    }
+
+    /// The source text that spans refer to, if it is available.
+    pub const fn source(&self) -> Option<&'de str> {
+        self.source
+    }
+}
+
+/// The elements of an array, the values of the properties of an object, and the arguments of a
+/// call that was not evaluated (such as an enum variant), in order, where the value of a
+/// shorthand property is its identifier.
+fn children(expr: &Expr) -> Vec<Cow<'_, Expr>> {
+    match expr {
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .flatten()
+            .map(|elem| Cow::Borrowed(&*elem.expr))
+            .collect(),
+        Expr::Object(object) => object
+            .props
+            .iter()
+            .filter_map(|prop_or_spread| match prop_or_spread {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::KeyValue(kvp) => Some(Cow::Borrowed(&*kvp.value)),
+                    Prop::Shorthand(ident) => Some(Cow::Owned(Expr::Ident(ident.clone()))),
+                    _ => None,
+                },
+                PropOrSpread::Spread(spread) => Some(Cow::Borrowed(&*spread.expr)),
+            })
+            .collect(),
+        Expr::Call(CallExpr { callee, args, .. }) => match callee {
+            Callee::Expr(callee) => Some(&**callee),
+            _ => None,
+        }
+        .into_iter()
+        .chain(args.iter().map(|arg| &*arg.expr))
+        .map(Cow::Borrowed)
+        .collect(),
+        Expr::New(NewExpr { callee, args, .. }) => std::iter::once(&**callee)
+            .chain(args.iter().flatten().map(|arg| &*arg.expr))
+            .map(Cow::Borrowed)
+            .collect(),
+        Expr::Paren(paren) => vec![Cow::Borrowed(&*paren.expr)],
+        _ => vec![],
+    }
+}
+
+/// The same expressions as `children`, where shorthand properties are first replaced with
+/// key-value properties so that their values can be replaced.
+fn children_mut(expr: &mut Expr) -> Vec<&mut Expr> {
+    match expr {
+        Expr::Array(array) => array
+            .elems
+            .iter_mut()
+            .flatten()
+            .map(|elem| &mut *elem.expr)
+            .collect(),
+        Expr::Object(object) => object
+            .props
+            .iter_mut()
+            .filter_map(|prop_or_spread| match prop_or_spread {
+                PropOrSpread::Prop(prop) => {
+                    if let Prop::Shorthand(ident) = &**prop {
+                        **prop = Prop::KeyValue(KeyValueProp {
+                            key: PropName::Ident(ident.clone().into()),
+                            value: Box::new(Expr::Ident(ident.clone())),
+                        });
+                    }
+
+                    match &mut **prop {
+                        Prop::KeyValue(kvp) => Some(&mut *kvp.value),
+                        _ => None,
+                    }
+                }
+                PropOrSpread::Spread(spread) => Some(&mut *spread.expr),
+            })
+            .collect(),
+        Expr::Call(CallExpr { callee, args, .. }) => match callee {
+            Callee::Expr(callee) => Some(&mut **callee),
+            _ => None,
+        }
+        .into_iter()
+        .chain(args.iter_mut().map(|arg| &mut *arg.expr))
+        .collect(),
+        Expr::New(NewExpr { callee, args, .. }) => std::iter::once(&mut **callee)
+            .chain(args.iter_mut().flatten().map(|arg| &mut *arg.expr))
+            .collect(),
+        Expr::Paren(paren) => vec![&mut *paren.expr],
+        _ => vec![],
+    }
+}
@@ -0,0 +1,419 @@
+use crate::error::Error;
+use serde::de::{Deserialize, Deserializer, Visitor};
+use swc_ecma_ast::{Expr, ExprOrSpread, Lit};
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+/// The number of components in the serde format of `time::OffsetDateTime`.
+#[cfg(feature = "time")]
+pub(crate) const TIME_COMPONENTS: usize = 9;
+/// The largest distance from the epoch that a JavaScript date can represent.
+const MAX_MILLIS: i64 = 8_640_000_000_000_000;
+
+/// A JavaScript `Date`, represented as milliseconds since the Unix epoch.
+///
+/// This can be deserialized from `new Date(...)` with a number of milliseconds, an ISO 8601
+/// string, or numeric date and time components. JavaScript interprets components and strings
+/// without an offset in the local time zone of the browser, which is not known here, so these
+/// are interpreted as UTC.
+///
+/// Dates can also be deserialized directly as integer milliseconds or as RFC 3339 strings, which
+/// means that types such as `chrono::DateTime<Utc>` can be used as targets. With the `time`
+/// feature, `time::OffsetDateTime` can also be used, in its default serde format. If the
+/// `serde-human-readable` feature of `time` is enabled, that format is a string that is not RFC
+/// 3339, so `time::serde::rfc3339` or `time::serde::timestamp::milliseconds` must be used instead.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Date {
+    millis: i64,
+}
+
+impl Date {
+    /// Returns `None` if the time is outside the range that JavaScript supports.
+    #[must_use]
+    pub const fn from_timestamp_millis(millis: i64) -> Option<Self> {
+        if millis.unsigned_abs() <= MAX_MILLIS.unsigned_abs() {
+            Some(Self { millis })
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub const fn timestamp_millis(self) -> i64 {
+        self.millis
+    }
+
+    /// Returns `None` if the date is outside the range that `chrono` supports.
+    #[cfg(feature = "chrono")]
+    #[must_use]
+    pub const fn to_chrono(self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp_millis(self.millis)
+    }
+
+    /// Returns `None` if the date is outside the range that `time` supports.
+    #[cfg(feature = "time")]
+    #[must_use]
+    pub fn to_time(self) -> Option<time::OffsetDateTime> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(i128::from(self.millis) * 1_000_000).ok()
+    }
+
+    /// The components that `time::OffsetDateTime` is deserialized from when its input is not a
+    /// string: the year, the day of the year, the hour, minute, second, and nanosecond, and the
+    /// hours, minutes, and seconds of the offset, which is zero.
+    #[cfg(feature = "time")]
+    pub(crate) fn time_components(self) -> Option<[i64; TIME_COMPONENTS]> {
+        let time = self.to_time()?;
+
+        Some([
+            time.year().into(),
+            time.ordinal().into(),
+            time.hour().into(),
+            time.minute().into(),
+            time.second().into(),
+            time.nanosecond().into(),
+            0,
+            0,
+            0,
+        ])
+    }
+}
+
+impl std::fmt::Display for Date {
+    /// Formats the date as JavaScript's `toISOString` does.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let days = self.millis.div_euclid(MILLIS_PER_DAY);
+        let millis = self.millis.rem_euclid(MILLIS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        if (0..=9999).contains(&year) {
+            write!(f, "{year:04}")?;
+        } else {
+            write!(f, "{year:+07}")?;
+        }
+
+        write!(
+            f,
+            "-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+            millis / 3_600_000,
+            millis / 60_000 % 60,
+            millis / 1000 % 60,
+            millis % 1000
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for Date {
+    fn from(value: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            millis: value.timestamp_millis(),
+        }
+    }
+}
+
+impl serde::Serialize for Date {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.millis)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_i64(DateVisitor)
+    }
+}
+
+struct DateVisitor;
+
+impl Visitor<'_> for DateVisitor {
+    type Value = Date;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a date")
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Date::from_timestamp_millis(v)
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        i64::try_from(v)
+            .ok()
+            .and_then(Date::from_timestamp_millis)
+            .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+    }
+}
+
+/// Read a `new Date(...)` expression with literal arguments.
+///
+/// Returns `None` if the expression is not a `Date` construction.
+pub(crate) fn from_expr(expr: &Expr) -> Option<Result<Date, Error>> {
+    let new = expr.as_new()?;
+
+    if new
+        .callee
+        .as_ident()
+        .is_none_or(|ident| ident.sym.as_str() != "Date")
+    {
+        return None;
+    }
+
+    let args = new.args.as_deref().unwrap_or_default();
+
+    let millis = match args {
+        [ExprOrSpread { spread: None, expr }] => match &**expr {
+            Expr::Lit(Lit::Str(str)) => Some(parse_iso(&str.value)),
+            other => crate::number::expr_to_f64(other).map(Some),
+        },
+        [_, _, ..] if args.len() <= 7 => args
+            .iter()
+            .map(|arg| {
+                arg.spread
+                    .is_none()
+                    .then(|| crate::number::expr_to_f64(&arg.expr))
+                    .flatten()
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|components| from_components(&components)),
+        _ => None,
+    };
+
+    Some(millis.map_or_else(
+        || Err(Error::UnexpectedExpr(expr.clone())),
+        |millis| {
+            millis
+                .and_then(time_clip)
+                .and_then(Date::from_timestamp_millis)
+                .ok_or_else(|| Error::InvalidDate(expr.clone()))
+        },
+    ))
+}
+
+/// Truncate a time value to an integer, failing if it is out of range.
+#[allow(clippy::cast_possible_truncation)]
+fn time_clip(millis: f64) -> Option<i64> {
+    #[allow(clippy::cast_precision_loss)]
+    let max = MAX_MILLIS as f64;
+
+    (millis.is_finite() && millis.abs() <= max).then(|| millis.trunc() as i64)
+}
+
+/// Combine year, month index, day, hours, minutes, seconds, and milliseconds.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn from_components(components: &[f64]) -> Option<f64> {
+    if !components.iter().all(|value| value.is_finite()) {
+        return None;
+    }
+
+    let component =
+        |index: usize, default: f64| components.get(index).map_or(default, |value| value.trunc());
+
+    let mut year = component(0, 0.0);
+    let month = component(1, 0.0);
+
+    if (0.0..=99.0).contains(&year) {
+        year += 1900.0;
+    }
+
+    // Anything further than this from the epoch would be out of range anyway.
+    if year.abs() > 400_000.0 || month.abs() > 4_800_000.0 {
+        return None;
+    }
+
+    let year = year as i64 + (month as i64).div_euclid(12);
+    let month = (month as i64).rem_euclid(12) + 1;
+    let days = days_from_civil(year, month, 1) as f64 + component(2, 1.0) - 1.0;
+
+    Some(
+        days * MILLIS_PER_DAY as f64
+            + component(3, 0.0) * 3_600_000.0
+            + component(4, 0.0) * 60_000.0
+            + component(5, 0.0) * 1000.0
+            + component(6, 0.0),
+    )
+}
+
+/// Parse the ISO 8601 subset that JavaScript's `Date` is required to accept.
+#[allow(clippy::cast_precision_loss)]
+fn parse_iso(input: &str) -> Option<f64> {
+    let mut parser = Parser { input, position: 0 };
+
+    let year = match parser.peek() {
+        Some(sign @ (b'+' | b'-')) => {
+            parser.position += 1;
+            let year = parser.digits(6)?;
+
+            // Negative zero is not a valid extended year.
+            if sign == b'-' {
+                if year == 0 {
+                    return None;
+                }
+
+                -year
+            } else {
+                year
+            }
+        }
+        _ => parser.digits(4)?,
+    };
+
+    let mut month = 1;
+    let mut day = 1;
+
+    if parser.eat(b'-') {
+        month = parser.digits(2)?;
+
+        if parser.eat(b'-') {
+            day = parser.digits(2)?;
+        }
+    }
+
+    if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    let mut time = 0;
+    let mut offset = 0;
+
+    if parser.eat(b'T') {
+        let hour = parser.digits(2)?;
+        parser.expect(b':')?;
+        let minute = parser.digits(2)?;
+        let second = if parser.eat(b':') {
+            parser.digits(2)?
+        } else {
+            0
+        };
+        let millis = if parser.eat(b'.') {
+            parser.fraction()?
+        } else {
+            0
+        };
+
+        if hour > 24 || minute > 59 || second > 59 || (hour == 24 && minute + second + millis > 0) {
+            return None;
+        }
+
+        time = ((hour * 60 + minute) * 60 + second) * 1000 + millis;
+
+        if !parser.eat(b'Z')
+            && let Some(sign @ (b'+' | b'-')) = parser.peek()
+        {
+            parser.position += 1;
+            let hours = parser.digits(2)?;
+            parser.expect(b':')?;
+            let minutes = parser.digits(2)?;
+
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+
+            offset = (hours * 60 + minutes) * 60_000 * if sign == b'-' { -1 } else { 1 };
+        }
+    }
+
+    (parser.position == input.len())
+        .then(|| (days_from_civil(year, month, day) * MILLIS_PER_DAY + time - offset) as f64)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matches = self.peek() == Some(byte);
+
+        if matches {
+            self.position += 1;
+        }
+
+        matches
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        self.eat(byte).then_some(())
+    }
+
+    fn digits(&mut self, count: usize) -> Option<i64> {
+        let digits = self.input.get(self.position..self.position + count)?;
+
+        if digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            self.position += count;
+            digits.parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Read fractional seconds as milliseconds, ignoring digits beyond the third.
+    fn fraction(&mut self) -> Option<i64> {
+        let start = self.position;
+
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+
+        let digits = &self.input[start..self.position];
+
+        if digits.is_empty() {
+            None
+        } else {
+            format!("{:0<3}", &digits[..digits.len().min(3)])
+                .parse()
+                .ok()
+        }
+    }
+}
+
+const fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+const fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The number of days since the epoch of a date in the proleptic Gregorian calendar.
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The year, month, and day of a number of days since the epoch.
+const fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+
+    (
+        year_of_era + era * 400 + if month <= 2 { 1 } else { 0 },
+        month,
+        day,
+    )
+}
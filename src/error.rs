@@ -1,4 +1,8 @@
+use crate::options::Limit;
+use crate::path::Path;
+use crate::value::Value;
 use serde::de::{Error as _, Unexpected};
+use swc_common::Span;
 use swc_ecma_ast::{
     BigInt, Expr, ExprOrSpread, JSXText, Lit, Number, Prop, PropName, Regex, SpreadElement,
 };
@@ -24,19 +28,57 @@ pub enum Error {
     UnexpectedJsxText(JSXText),
     #[error("Unexpected regex")]
     UnexpectedRegex(Regex),
+    #[error("Unsupported regex flag: {0}")]
+    UnsupportedRegexFlag(char),
+    #[cfg(feature = "regex")]
+    #[error("Invalid regex")]
+    InvalidRegex(::regex::Error),
     #[error("Unexpected spread")]
     UnexpectedSpread(SpreadElement),
     #[error("Unexpected property")]
     UnexpectedProp(Box<Prop>),
     #[error("Unexpected expression")]
     UnexpectedExpr(Expr),
+    #[error("Invalid date")]
+    InvalidDate(Expr),
+    #[error("Invalid encoded string")]
+    InvalidEncodedString(Expr),
+    #[error("Unexpected enum object: {0}")]
+    UnexpectedEnumObject(String),
+    #[error("Unknown binding: {0}")]
+    UnknownBinding(String),
+    #[error("Cyclic binding: {0}")]
+    CyclicBinding(String),
+    #[error("Reassigned binding: {0}")]
+    ReassignedBinding(String),
     #[error("Expected field value")]
     ExpectedFieldValue,
+    #[error("Exceeded limit: {0}")]
+    LimitExceeded(Limit),
+    #[error("Source text unavailable")]
+    SourceUnavailable(Span),
+    #[error("Value cannot be represented as JSON")]
+    UnrepresentableJson(Box<Value>),
     #[error("Serde error")]
     Serde(serde::de::value::Error),
+    #[error("{source} at {path}")]
+    Located {
+        path: Path,
+        span: Span,
+        source: Box<Self>,
+    },
 }
 
 impl Error {
+    /// The location of the error, if one was recorded.
+    #[must_use]
+    pub const fn location(&self) -> Option<(&Path, Span)> {
+        match self {
+            Self::Located { path, span, .. } => Some((path, *span)),
+            _ => None,
+        }
+    }
+
     pub(super) fn unexpected_lit(lit: &Lit, expected: &str) -> Self {
         match lit {
             Lit::Bool(bool) => Self::invalid_type(Unexpected::Bool(bool.value), &expected),
@@ -53,6 +95,12 @@ impl Error {
     }
 }
 
+impl serde::ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Serde(<serde::de::value::Error as serde::ser::Error>::custom(msg))
+    }
+}
+
 impl serde::de::Error for Error {
     fn custom<T: std::fmt::Display>(msg: T) -> Self {
         Self::Serde(serde::de::value::Error::custom(msg))
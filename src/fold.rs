@@ -0,0 +1,575 @@
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::float_cmp
+)]
+use std::cmp::Ordering;
+use swc_common::Span;
+use swc_ecma_ast::{BigIntValue, BinaryOp, Expr, Ident, Lit, UnaryOp};
+
+/// The largest big integer, in bits, that folding will produce.
+///
+/// Exponentiation and left shifts can otherwise produce enormous values from short input.
+const MAX_BIG_INT_BITS: u64 = 1 << 16;
+
+/// The deepest that operators may be nested, through operands and the bindings they refer to,
+/// when folding them.
+///
+/// Folding is recursive, so this keeps long chains such as `a + b + c + ...` from exhausting the
+/// stack.
+pub const MAX_DEPTH: usize = 256;
+
+/// Apply a unary operator, returning `None` where JavaScript would throw.
+pub fn unary(op: UnaryOp, arg: Constant) -> Option<Constant> {
+    match op {
+        UnaryOp::Bang => Some(Constant::Bool(!arg.to_bool())),
+        UnaryOp::Minus => match arg.into_numeric() {
+            Constant::BigInt(value) => Some(Constant::BigInt(-value)),
+            other => other.into_number().map(|value| Constant::Number(-value)),
+        },
+        UnaryOp::Plus => arg.into_number().map(Constant::Number),
+        UnaryOp::Tilde => match arg.into_numeric() {
+            Constant::BigInt(value) => Some(Constant::BigInt(-value - 1)),
+            other => other
+                .into_number()
+                .map(|value| Constant::Number(f64::from(!to_int32(value)))),
+        },
+        UnaryOp::Void => Some(Constant::Undefined),
+        UnaryOp::TypeOf | UnaryOp::Delete => None,
+    }
+}
+
+/// Apply a binary operator other than a logical one, returning `None` where JavaScript would
+/// throw.
+pub fn binary(op: BinaryOp, left: Constant, right: Constant) -> Option<Constant> {
+    match op {
+        BinaryOp::Add => match (left, right) {
+            (left @ Constant::String(_), right) | (left, right @ Constant::String(_)) => Some(
+                Constant::String(left.to_js_string() + right.to_js_string().as_str()),
+            ),
+            (left, right) => arithmetic(op, left, right),
+        },
+        BinaryOp::Sub
+        | BinaryOp::Mul
+        | BinaryOp::Div
+        | BinaryOp::Mod
+        | BinaryOp::Exp
+        | BinaryOp::LShift
+        | BinaryOp::RShift
+        | BinaryOp::ZeroFillRShift
+        | BinaryOp::BitAnd
+        | BinaryOp::BitOr
+        | BinaryOp::BitXor => arithmetic(op, left, right),
+        BinaryOp::EqEqEq => Some(Constant::Bool(strict_equals(&left, &right))),
+        BinaryOp::NotEqEq => Some(Constant::Bool(!strict_equals(&left, &right))),
+        BinaryOp::EqEq => Some(Constant::Bool(loose_equals(left, right))),
+        BinaryOp::NotEq => Some(Constant::Bool(!loose_equals(left, right))),
+        BinaryOp::Lt => Some(Constant::Bool(less_than(left, right) == Some(true))),
+        BinaryOp::Gt => Some(Constant::Bool(less_than(right, left) == Some(true))),
+        BinaryOp::LtEq => Some(Constant::Bool(less_than(right, left) == Some(false))),
+        BinaryOp::GtEq => Some(Constant::Bool(less_than(left, right) == Some(false))),
+        BinaryOp::LogicalOr
+        | BinaryOp::LogicalAnd
+        | BinaryOp::NullishCoalescing
+        | BinaryOp::In
+        | BinaryOp::InstanceOf => None,
+    }
+}
+
+/// Apply an arithmetic or bitwise operator to two numbers or two big integers.
+fn arithmetic(op: BinaryOp, left: Constant, right: Constant) -> Option<Constant> {
+    match (left.into_numeric(), right.into_numeric()) {
+        (Constant::Number(left), Constant::Number(right)) => {
+            Some(Constant::Number(number_arithmetic(op, left, right)))
+        }
+        (Constant::BigInt(left), Constant::BigInt(right)) => {
+            big_int_arithmetic(op, left, right).map(Constant::BigInt)
+        }
+        // Mixing numbers and big integers is a type error.
+        _ => None,
+    }
+}
+
+fn number_arithmetic(op: BinaryOp, left: f64, right: f64) -> f64 {
+    let shift = to_uint32(right) & 31;
+
+    match op {
+        BinaryOp::Add => left + right,
+        BinaryOp::Sub => left - right,
+        BinaryOp::Mul => left * right,
+        BinaryOp::Div => left / right,
+        BinaryOp::Mod => left % right,
+        // Unlike `powf`, JavaScript gives `NaN` for `1 ** NaN` and `1 ** Infinity`.
+        BinaryOp::Exp if right.is_nan() || (left.abs() == 1.0 && right.is_infinite()) => f64::NAN,
+        BinaryOp::Exp => left.powf(right),
+        BinaryOp::LShift => f64::from(to_int32(left).wrapping_shl(shift)),
+        BinaryOp::RShift => f64::from(to_int32(left) >> shift),
+        BinaryOp::ZeroFillRShift => f64::from(to_uint32(left) >> shift),
+        BinaryOp::BitAnd => f64::from(to_int32(left) & to_int32(right)),
+        BinaryOp::BitOr => f64::from(to_int32(left) | to_int32(right)),
+        BinaryOp::BitXor => f64::from(to_int32(left) ^ to_int32(right)),
+        _ => f64::NAN,
+    }
+}
+
+fn big_int_arithmetic(op: BinaryOp, left: BigIntValue, right: BigIntValue) -> Option<BigIntValue> {
+    let zero = BigIntValue::from(0);
+
+    match op {
+        BinaryOp::Add => Some(left + right),
+        BinaryOp::Sub => Some(left - right),
+        BinaryOp::Mul => Some(left * right),
+        BinaryOp::Div if right != zero => Some(left / right),
+        BinaryOp::Mod if right != zero => Some(left % right),
+        BinaryOp::Exp if right >= zero => {
+            let exponent = u32::try_from(&right).ok()?;
+
+            (left.bits().saturating_mul(u64::from(exponent)) <= MAX_BIG_INT_BITS)
+                .then(|| left.pow(exponent))
+        }
+        BinaryOp::LShift => shift_left(left, &right),
+        BinaryOp::RShift => shift_left(left, &-right),
+        BinaryOp::BitAnd => Some(left & right),
+        BinaryOp::BitOr => Some(left | right),
+        BinaryOp::BitXor => Some(left ^ right),
+        // Division by zero, negative exponents, and unsigned shifts are errors.
+        _ => None,
+    }
+}
+
+/// Shift a big integer left, or right for a negative shift, rounding towards negative infinity.
+fn shift_left(value: BigIntValue, shift: &BigIntValue) -> Option<BigIntValue> {
+    let shift = i64::try_from(shift).unwrap_or_else(|_| {
+        if *shift < BigIntValue::from(0) {
+            i64::MIN
+        } else {
+            i64::MAX
+        }
+    });
+
+    if shift >= 0 {
+        let shift = shift.unsigned_abs();
+
+        (value.bits().saturating_add(shift) <= MAX_BIG_INT_BITS).then(|| value << shift)
+    } else {
+        // Shifting by more than the width of the value gives zero or negative one.
+        let shift = shift.unsigned_abs().min(value.bits() + 1);
+
+        Some(value >> shift)
+    }
+}
+
+fn strict_equals(left: &Constant, right: &Constant) -> bool {
+    match (left, right) {
+        (Constant::Undefined, Constant::Undefined) | (Constant::Null, Constant::Null) => true,
+        (Constant::Number(left), Constant::Number(right)) => left == right,
+        (Constant::Bool(left), Constant::Bool(right)) => left == right,
+        (Constant::String(left), Constant::String(right)) => left == right,
+        (Constant::BigInt(left), Constant::BigInt(right)) => left == right,
+        _ => false,
+    }
+}
+
+fn loose_equals(left: Constant, right: Constant) -> bool {
+    match (left, right) {
+        (Constant::Undefined | Constant::Null, Constant::Undefined | Constant::Null) => true,
+        (Constant::Undefined | Constant::Null, _) | (_, Constant::Undefined | Constant::Null) => {
+            false
+        }
+        (Constant::Bool(left), right) => loose_equals(Constant::Number(f64::from(left)), right),
+        (left, Constant::Bool(right)) => loose_equals(left, Constant::Number(f64::from(right))),
+        (Constant::Number(number), Constant::String(string))
+        | (Constant::String(string), Constant::Number(number)) => {
+            number == crate::number::string_to_number(&string)
+        }
+        (Constant::BigInt(big_int), Constant::String(string))
+        | (Constant::String(string), Constant::BigInt(big_int)) => {
+            string_to_big_int(&string).is_some_and(|value| value == big_int)
+        }
+        (Constant::BigInt(big_int), Constant::Number(number))
+        | (Constant::Number(number), Constant::BigInt(big_int)) => {
+            compare_big_int(&big_int, number) == Some(Ordering::Equal)
+        }
+        (left, right) => strict_equals(&left, &right),
+    }
+}
+
+/// Whether the left operand is less than the right, or `None` if they cannot be compared.
+fn less_than(left: Constant, right: Constant) -> Option<bool> {
+    match (left, right) {
+        // Strings are compared by UTF-16 code units.
+        (Constant::String(left), Constant::String(right)) => {
+            Some(left.encode_utf16().lt(right.encode_utf16()))
+        }
+        (Constant::BigInt(left), Constant::String(right)) => {
+            string_to_big_int(&right).map(|right| left < right)
+        }
+        (Constant::String(left), Constant::BigInt(right)) => {
+            string_to_big_int(&left).map(|left| left < right)
+        }
+        (left, right) => match (left.into_numeric(), right.into_numeric()) {
+            (Constant::BigInt(left), Constant::BigInt(right)) => Some(left < right),
+            (Constant::BigInt(left), Constant::Number(right)) => {
+                compare_big_int(&left, right).map(Ordering::is_lt)
+            }
+            (Constant::Number(left), Constant::BigInt(right)) => {
+                compare_big_int(&right, left).map(Ordering::is_gt)
+            }
+            (left, right) => {
+                let (left, right) = (left.into_number()?, right.into_number()?);
+
+                (!left.is_nan() && !right.is_nan()).then_some(left < right)
+            }
+        },
+    }
+}
+
+/// Compare a big integer with a number exactly.
+fn compare_big_int(big_int: &BigIntValue, number: f64) -> Option<Ordering> {
+    if number.is_nan() {
+        None
+    } else if number.is_infinite() {
+        Some(if number > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        })
+    } else {
+        // Every finite number has an exact decimal representation.
+        let floor = number.floor();
+        let floor_big_int = format!("{floor:.0}").parse::<BigIntValue>().ok()?;
+
+        Some(match big_int.cmp(&floor_big_int) {
+            Ordering::Equal if number > floor => Ordering::Less,
+            other => other,
+        })
+    }
+}
+
+/// Convert a string to a big integer as JavaScript's `StringToBigInt` does.
+fn string_to_big_int(string: &str) -> Option<BigIntValue> {
+    let string = string.trim_matches(crate::number::is_js_whitespace);
+
+    if string.is_empty() {
+        return Some(BigIntValue::from(0));
+    }
+
+    let (digits, radix) = match string.get(..2) {
+        Some("0x" | "0X") => (&string[2..], 16),
+        Some("0o" | "0O") => (&string[2..], 8),
+        Some("0b" | "0B") => (&string[2..], 2),
+        _ => (string.strip_prefix(['+', '-']).unwrap_or(string), 10),
+    };
+
+    if digits.is_empty() || !digits.chars().all(|ch| ch.is_digit(radix)) {
+        return None;
+    }
+
+    if radix == 10 {
+        string.parse().ok()
+    } else {
+        BigIntValue::parse_bytes(digits.as_bytes(), radix)
+    }
+}
+
+fn to_int32(value: f64) -> i32 {
+    to_uint32(value) as i32
+}
+
+fn to_uint32(value: f64) -> u32 {
+    if value.is_finite() {
+        value.trunc().rem_euclid(4_294_967_296.0) as u32
+    } else {
+        0
+    }
+}
+
+/// A JavaScript primitive value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Constant {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    BigInt(BigIntValue),
+}
+
+impl Constant {
+    pub fn from_expr(expr: &Expr) -> Option<Self> {
+        match expr {
+            Expr::Lit(Lit::Null(_)) => Some(Self::Null),
+            Expr::Lit(Lit::Bool(bool)) => Some(Self::Bool(bool.value)),
+            Expr::Lit(Lit::Num(number)) => Some(Self::Number(number.value)),
+            Expr::Lit(Lit::Str(str)) => Some(Self::String(str.value.to_string())),
+            Expr::Lit(Lit::BigInt(big_int)) => Some(Self::BigInt((*big_int.value).clone())),
+            Expr::Ident(ident) => match ident.sym.as_str() {
+                "undefined" => Some(Self::Undefined),
+                "NaN" => Some(Self::Number(f64::NAN)),
+                "Infinity" => Some(Self::Number(f64::INFINITY)),
+                _ => None,
+            },
+            Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+                .quasis
+                .iter()
+                .map(|quasi| quasi.cooked.as_deref())
+                .collect::<Option<String>>()
+                .map(Self::String),
+            _ => None,
+        }
+    }
+
+    pub fn into_expr(self, span: Span) -> Expr {
+        match self {
+            Self::Undefined => Expr::Ident(Ident::new_no_ctxt("undefined".into(), span)),
+            Self::Null => Expr::Lit(Lit::Null(swc_ecma_ast::Null { span })),
+            Self::Bool(value) => Expr::Lit(Lit::Bool(swc_ecma_ast::Bool { span, value })),
+            Self::Number(value) => Expr::Lit(Lit::Num(swc_ecma_ast::Number {
+                span,
+                value,
+                raw: Some(crate::number::to_js_string(value).into()),
+            })),
+            Self::String(value) => Expr::Lit(Lit::Str(swc_ecma_ast::Str {
+                span,
+                value: value.into(),
+                raw: None,
+            })),
+            Self::BigInt(value) => Expr::Lit(Lit::BigInt(swc_ecma_ast::BigInt {
+                span,
+                value: Box::new(value),
+                raw: None,
+            })),
+        }
+    }
+
+    /// The result of `typeof` for this value.
+    pub const fn type_of(&self) -> &'static str {
+        match self {
+            Self::Undefined => "undefined",
+            Self::Null => "object",
+            Self::Bool(_) => "boolean",
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+            Self::BigInt(_) => "bigint",
+        }
+    }
+
+    pub fn to_bool(&self) -> bool {
+        match self {
+            Self::Undefined | Self::Null => false,
+            Self::Bool(value) => *value,
+            Self::Number(value) => *value != 0.0 && !value.is_nan(),
+            Self::String(value) => !value.is_empty(),
+            Self::BigInt(value) => *value != BigIntValue::from(0),
+        }
+    }
+
+    /// Convert to a number or big integer, as JavaScript's `ToNumeric` does.
+    fn into_numeric(self) -> Self {
+        match self {
+            Self::Undefined => Self::Number(f64::NAN),
+            Self::Null => Self::Number(0.0),
+            Self::Bool(value) => Self::Number(f64::from(value)),
+            Self::String(value) => Self::Number(crate::number::string_to_number(&value)),
+            number @ (Self::Number(_) | Self::BigInt(_)) => number,
+        }
+    }
+
+    /// Convert to a number, or `None` for a big integer, which JavaScript does not convert
+    /// implicitly.
+    fn into_number(self) -> Option<f64> {
+        match self.into_numeric() {
+            Self::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn to_js_string(&self) -> String {
+        match self {
+            Self::Undefined => "undefined".to_string(),
+            Self::Null => "null".to_string(),
+            Self::Bool(value) => value.to_string(),
+            Self::Number(value) => crate::number::to_js_string(*value),
+            Self::String(value) => value.clone(),
+            Self::BigInt(value) => value.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Options;
+    use crate::error::Error;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config {
+        name: String,
+        ttl: u32,
+        flags: Vec<bool>,
+        mask: i32,
+    }
+
+    #[test]
+    fn fold_constants() -> Result<(), Error> {
+        let options = Options::new().fold_constants(true);
+
+        assert_eq!(
+            options.from_str::<Config>(
+                r#"{ name: "a" + "b", ttl: 60 * 60 * 24, flags: [!0, !1, (0, true)], mask: 1 << 4 | 1 }"#
+            )?,
+            Config {
+                name: "ab".to_string(),
+                ttl: 86_400,
+                flags: vec![true, false, true],
+                mask: 17,
+            }
+        );
+        assert_eq!(
+            options.from_str::<Vec<bool>>(
+                r#"[typeof undefined === "undefined", typeof 1 === "y", null == undefined, "1" == 1, 1n == 1, "10" < "9", 10 < 9, NaN <= NaN]"#
+            )?,
+            vec![true, false, true, true, true, true, false, false]
+        );
+        assert_eq!(
+            options.from_str::<Vec<String>>(
+                r#"[1 + "2", 1e21 + "", 1 / 3 + "", -0 + "", "" || "x", null ?? "d", true ? "yes" : "no", `a` + 1n, 7n / 2n + "", (-7n >> 1n) + ""]"#
+            )?,
+            ["12", "1e+21", "0.3333333333333333", "0", "x", "d", "yes", "a1", "3", "-4"]
+        );
+        assert_eq!(
+            options
+                .from_str::<Vec<f64>>(r#"["3" * "4", 0.1 + 0.2, -7 % 3, 2 ** 10, -1 >>> 0, ~5]"#)?,
+            vec![12.0, 0.1 + 0.2, -1.0, 1024.0, 4_294_967_295.0, -6.0]
+        );
+        assert_eq!(
+            options
+                .from_str::<(f64, Option<u8>, String)>("[1 / 0, void 0, 2n ** 64n + 1n + \"\"]")?,
+            (f64::INFINITY, None, "18446744073709551617".to_string())
+        );
+        assert_eq!(
+            options.from_str::<Vec<i64>>("(1, [-(2), 1 ? 3 : x, 0 && y])")?,
+            vec![-2, 3, 0]
+        );
+
+        assert!(options.from_str::<u32>("1 + x").is_err());
+        assert!(options.from_str::<u32>("1n + 1").is_err());
+        assert!(options.from_str::<u32>("1n / 0n").is_err());
+        assert!(options.from_str::<String>("2n ** 100000n + \"\"").is_err());
+        assert!(crate::from_str::<u32>("1 + 1").is_err());
+        assert!(crate::from_str::<u32>("true ? 1 : 2").is_err());
+        assert!(crate::from_str::<u32>("0 || 1").is_err());
+        // Identifiers that are not bound may be globals of the host.
+        assert!(
+            options
+                .from_str::<bool>(r#"typeof x === "undefined""#)
+                .is_err()
+        );
+        assert!(
+            options
+                .from_str::<u32>(r#"typeof window === "object" ? 1 : 2"#)
+                .is_err()
+        );
+        assert!(
+            options
+                .from_str::<bool>(r#"typeof module !== "undefined" && module.exports"#)
+                .is_err()
+        );
+        assert!(matches!(
+            options
+                .clone()
+                .max_fold_steps(2)
+                .from_str::<u32>("1 + 2 + 3 + 4"),
+            Err(Error::LimitExceeded(crate::options::Limit::FoldSteps(2)))
+        ));
+        assert_eq!(
+            options
+                .clone()
+                .max_fold_steps(3)
+                .from_str::<u32>("1 + 2 + 3 + 4")?,
+            10
+        );
+
+        let chain = |length: usize| vec!["1"; length].join(" + ");
+
+        assert_eq!(options.from_str::<usize>(&chain(250))?, 250);
+        assert!(matches!(
+            options.from_str::<usize>(&chain(300)),
+            Err(Error::LimitExceeded(crate::options::Limit::Depth(
+                super::MAX_DEPTH
+            )))
+        ));
+
+        // Each binding refers to the previous one twice, so folding takes exponential time.
+        let source = std::iter::once("var a0 = 1;".to_string())
+            .chain((1..24).map(|index| format!("var a{index} = a{} + a{};", index - 1, index - 1)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let script = options.parse_script(&source)?;
+
+        assert!(matches!(
+            script.get::<f64>("a23"),
+            Err(Error::LimitExceeded(crate::options::Limit::FoldSteps(
+                crate::options::Limits::DEFAULT_MAX_FOLD_STEPS
+            )))
+        ));
+        assert_eq!(script.get::<f64>("a10")?, 1024.0);
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Borrowed<'a> {
+        a: &'a str,
+        b: String,
+    }
+
+    #[test]
+    fn fold_in_context() -> Result<(), Error> {
+        let options = Options::new().fold_constants(true).global("G", 1);
+
+        // Only the values that are folded are copied.
+        let expr = options.parse_expr("{ a: 'x', b: 'y' + 'z' }")?;
+
+        assert_eq!(
+            options.from_expr::<Borrowed<'_>>(&expr)?,
+            Borrowed {
+                a: "x",
+                b: "yz".to_string(),
+            }
+        );
+
+        let script = options.parse_script(
+            "
+            var x = 1;
+            var o = {};
+            function f() {}
+            var types = [typeof x, typeof o, typeof undefined, typeof G, typeof (0, x), typeof typeof x];
+            var declared = typeof f;
+            var host = typeof y;
+            ",
+        )?;
+
+        assert_eq!(
+            script.get::<Vec<String>>("types")?,
+            [
+                "number",
+                "object",
+                "undefined",
+                "number",
+                "number",
+                "string"
+            ]
+        );
+        assert!(script.get::<String>("declared").is_err());
+        assert!(script.get::<String>("host").is_err());
+        assert_eq!(
+            options
+                .parse_script("var t = (function (x) { return typeof x; })(1);")?
+                .get::<String>("t")?,
+            "number"
+        );
+
+        Ok(())
+    }
+}
@@ -1,21 +1,50 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery, rust_2018_idioms)]
 #![allow(clippy::missing_errors_doc)]
 #![forbid(unsafe_code)]
+use serde::de::value::{BorrowedStrDeserializer, SeqAccessDeserializer, UnitDeserializer};
 use serde::de::{
     EnumAccess, Error as _, IntoDeserializer, MapAccess, SeqAccess, Unexpected, VariantAccess,
     Visitor,
 };
 use std::borrow::Cow;
-use swc_ecma_ast::{ArrayLit, Expr, ExprOrSpread, Lit, ObjectLit, Prop, PropName, PropOrSpread};
+use swc_common::Spanned as _;
+use swc_ecma_ast::{
+    ArrayLit, Bool, CallExpr, Callee, ComputedPropName, Expr, ExprOrSpread, Lit, MemberExpr,
+    MemberProp, NewExpr, ObjectLit, Prop, PropName, PropOrSpread,
+};
 
+mod builtin;
+mod bytes;
+mod context;
+pub mod date;
 pub mod error;
+mod fold;
 mod number;
-
+pub mod options;
+pub mod path;
+pub mod raw;
+pub mod regex;
+pub mod script;
+pub mod spanned;
+pub mod value;
+pub mod warning;
+
+pub use crate::regex::Regex;
+pub use date::Date;
+pub use options::Options;
+pub use raw::{RawExpr, RawJs};
+pub use script::Script;
+pub use spanned::Spanned;
+pub use value::Value;
+
+use context::Context;
 use error::Error;
+use path::{Path, Segment};
+use warning::{Warning, WarningKind};
 
 #[cfg(feature = "parser")]
 pub fn from_str<'a: 'de, 'de, T: serde::Deserialize<'de>>(expr_str: &'a str) -> Result<T, Error> {
-    from_str_with_version(expr_str, swc_ecma_ast::EsVersion::default())
+    Options::new().from_str(expr_str)
 }
 
 #[cfg(feature = "parser")]
@@ -23,33 +52,189 @@ pub fn from_str_with_version<'a: 'de, 'de, T: serde::Deserialize<'de>>(
     expr_str: &'a str,
     version: swc_ecma_ast::EsVersion,
 ) -> Result<T, Error> {
+    Options::new().version(version).from_str(expr_str)
+}
+
+/// Deserialize the string, also returning warnings for any lossy conversions.
+///
+/// See [`from_expr_with_warnings`] for the conversions that are reported.
+#[cfg(feature = "parser")]
+pub fn from_str_with_warnings<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+    expr_str: &'a str,
+) -> Result<(T, Vec<Warning>), Error> {
+    Options::new().from_str_with_warnings(expr_str)
+}
+
+/// Deserialize the string, also returning the paths of all values ignored by the target type.
+#[cfg(feature = "parser")]
+pub fn from_str_with_ignored<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+    expr_str: &'a str,
+) -> Result<(T, Vec<Path>), Error> {
+    Options::new().from_str_with_ignored(expr_str)
+}
+
+/// Deserialize the exports of a `CommonJS` module, such as a `babel.config.js` file.
+///
+/// See [`Script::exports`] for how assignments to `module.exports` and `exports` are merged.
+#[cfg(feature = "parser")]
+pub fn from_commonjs_str<T: serde::de::DeserializeOwned>(source: &str) -> Result<T, Error> {
+    Options::new().from_commonjs_str(source)
+}
+
+#[cfg(feature = "parser")]
+fn parse_expr(expr_str: &str, version: swc_ecma_ast::EsVersion) -> Result<Box<Expr>, Error> {
+    parser(expr_str, version)
+        .parse_expr()
+        .map_err(Error::EcmaParse)
+}
+
+#[cfg(feature = "parser")]
+fn parse_program(
+    source: &str,
+    version: swc_ecma_ast::EsVersion,
+) -> Result<swc_ecma_ast::Program, Error> {
+    parser(source, version)
+        .parse_program()
+        .map_err(Error::EcmaParse)
+}
+
+#[cfg(feature = "parser")]
+fn parser(
+    source: &str,
+    version: swc_ecma_ast::EsVersion,
+) -> swc_ecma_parser::Parser<swc_ecma_parser::Lexer<'_>> {
     let lexer = swc_ecma_parser::Lexer::new(
         swc_ecma_parser::Syntax::Es(swc_ecma_parser::EsSyntax::default()),
         version,
         swc_ecma_parser::StringInput::new(
-            expr_str,
+            source,
             swc_common::BytePos(0),
-            swc_common::BytePos(u32::try_from(expr_str.len()).unwrap_or(u32::MAX)),
+            swc_common::BytePos(u32::try_from(source.len()).unwrap_or(u32::MAX)),
         ),
         None,
     );
 
-    let mut parser = swc_ecma_parser::Parser::new_from(lexer);
-    let expr = parser.parse_expr().map_err(Error::EcmaParse)?;
-
-    T::deserialize(Deserializer {
-        expr: std::borrow::Cow::Owned(*expr),
-    })
+    swc_ecma_parser::Parser::new_from(lexer)
 }
 
 pub fn from_expr<'a: 'de, 'de, T: serde::Deserialize<'de>>(expr: &'a Expr) -> Result<T, Error> {
-    T::deserialize(Deserializer {
-        expr: std::borrow::Cow::Borrowed(expr),
-    })
+    Options::new().from_expr(expr)
+}
+
+/// Deserialize the expression, also returning warnings for any lossy conversions.
+///
+/// Warnings are reported when a number is truncated to fit an `f32`, when an identifier is
+/// accepted as a string, and when a `null` is treated as an absent value by a self-describing
+/// type (such as `serde_json::Value`).
+pub fn from_expr_with_warnings<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+    expr: &'a Expr,
+) -> Result<(T, Vec<Warning>), Error> {
+    Options::new().from_expr_with_warnings(expr)
+}
+
+/// Deserialize the expression, also returning the paths of all values ignored by the target type.
+///
+/// For a struct that does not deny unknown fields, these are the paths of the object keys that
+/// do not correspond to any of its fields.
+pub fn from_expr_with_ignored<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+    expr: &'a Expr,
+) -> Result<(T, Vec<Path>), Error> {
+    Options::new().from_expr_with_ignored(expr)
+}
+
+/// Deserialize the expression, continuing past recoverable mismatches, and return every problem.
+///
+/// Mismatched scalars, strings, and arrays are recorded and replaced by a placeholder, so that
+/// problems in sibling fields and array elements are all reported. Errors that cannot be recovered
+/// from (such as missing fields) end the pass. Every error is returned as an [`Error::Located`]
+/// with the path and span of the expression it came from.
+#[must_use]
+pub fn validate<'a: 'de, 'de, T: serde::Deserialize<'de>>(expr: &'a Expr) -> Vec<Error> {
+    Options::new().validate::<T>(expr)
 }
 
 pub struct Deserializer<'de> {
     expr: std::borrow::Cow<'de, Expr>,
+    context: Context<'de>,
+}
+
+impl<'de> Deserializer<'de> {
+    const fn new(expr: Cow<'de, Expr>, context: Context<'de>) -> Self {
+        Self { expr, context }
+    }
+
+    fn recover<T, F: FnOnce() -> T>(
+        &self,
+        result: Result<T, Error>,
+        placeholder: F,
+    ) -> Result<T, Error> {
+        self.context.recover(result, self.expr.span(), placeholder)
+    }
+
+    /// A deserializer for the value of the expression, after resolving identifiers bound in the
+    /// script being deserialized and evaluating calls.
+    fn resolved(expr: Cow<'de, Expr>, context: Context<'de>) -> Result<Self, Error> {
+        context
+            .evaluate(expr)
+            .map(|(expr, context)| Self::new(expr, context))
+    }
+
+    /// A member or call that could not be evaluated, read as the enum variant it names when
+    /// [`Options::expressions_as_variants`] is set. A member is seen as the name of the variant
+    /// and a call as a map with a single entry, as `{ Variant: payload }` would be. Since types
+    /// that buffer their input may not deserialize it as an enum, a warning is reported.
+    fn deserialize_variant<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let span = self.expr.span();
+
+        match &self.expr {
+            Cow::Borrowed(expr @ Expr::Member(_)) => {
+                let variant = variant_name(expr, self.context.options())?;
+
+                self.context.warn(
+                    || WarningKind::ExpressionAsVariant(variant.to_string()),
+                    span,
+                );
+                visitor.visit_borrowed_str(variant)
+            }
+            Cow::Owned(expr @ Expr::Member(_)) => {
+                let variant = variant_name(expr, self.context.options())?;
+
+                self.context.warn(
+                    || WarningKind::ExpressionAsVariant(variant.to_string()),
+                    span,
+                );
+                visitor.visit_str(variant)
+            }
+            _ => {
+                let variant = Enum::from_call(self.expr, &self.context)?;
+
+                self.context.warn(
+                    || WarningKind::ExpressionAsVariant(variant.key.to_string()),
+                    span,
+                );
+                visitor.visit_map(VariantMap::new(variant))
+            }
+        }
+    }
+
+    /// A deserializer for a negated or parenthesized number literal as a plain literal.
+    fn signed_number(&self) -> Option<Self> {
+        number::signed_literal(&self.expr).map(|number| {
+            Self::new(
+                Cow::Owned(Expr::Lit(Lit::Num(number))),
+                self.context.clone(),
+            )
+        })
+    }
+
+    /// Count the bytes of a string or identifier against the limit.
+    fn consume_string(&self) -> Result<(), Error> {
+        match &*self.expr {
+            Expr::Lit(Lit::Str(str)) => self.context.consume_string(str.value.len()),
+            Expr::Ident(ident) => self.context.consume_string(ident.sym.len()),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
@@ -60,37 +245,125 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
     }
 
     fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_any(visitor);
+        }
+
         match &*self.expr {
             Expr::Array(_) => self.deserialize_seq(visitor),
             Expr::Object(_) => self.deserialize_map(visitor),
+            Expr::Paren(_) => {
+                let expr = match self.expr {
+                    Cow::Borrowed(Expr::Paren(paren)) => Cow::Borrowed(&*paren.expr),
+                    Cow::Owned(Expr::Paren(paren)) => Cow::Owned(*paren.expr),
+                    other => other,
+                };
+
+                Self::new(expr, self.context).deserialize_any(visitor)
+            }
+            Expr::New(_) if is_new(&self.expr, &["Map"]) => self.deserialize_map(visitor),
+            Expr::New(_) if is_new(&self.expr, &["Set"]) => self.deserialize_seq(visitor),
+            Expr::New(_) if is_new(&self.expr, bytes::TYPED_ARRAYS) => {
+                self.deserialize_seq(visitor)
+            }
+            Expr::New(_) if is_new(&self.expr, &["ArrayBuffer"]) => {
+                self.deserialize_byte_buf(visitor)
+            }
+            Expr::New(_) if is_new(&self.expr, &["Date"]) => self.deserialize_string(visitor),
             Expr::Lit(lit) => match lit {
                 Lit::Bool(bool) => visitor.visit_bool(bool.value),
                 Lit::Num(number) => {
-                    if number::is_integer(number) {
-                        self.deserialize_i64(visitor)
+                    // Non-negative integers are visited as unsigned, as in `serde_json`, since
+                    // variant indices and most visitors for unsigned types only accept those.
+                    if let Some(value) = number::number_to_u64(number) {
+                        visitor.visit_u64(value)
+                    } else if let Some(value) = number::number_to_i64(number) {
+                        visitor.visit_i64(value)
+                    } else {
+                        visitor.visit_f64(number.value)
+                    }
+                }
+                Lit::BigInt(big_int) => {
+                    if let Ok(value) = i64::try_from(&*big_int.value) {
+                        visitor.visit_i64(value)
+                    } else if let Ok(value) = u64::try_from(&*big_int.value) {
+                        visitor.visit_u64(value)
+                    } else if let Ok(value) = i128::try_from(&*big_int.value) {
+                        visitor.visit_i128(value)
+                    } else if let Ok(value) = u128::try_from(&*big_int.value) {
+                        visitor.visit_u128(value)
                     } else {
-                        self.deserialize_f64(visitor)
+                        self.recover(Err(Error::unexpected_lit(lit, "integer")), || ())?;
+
+                        visitor.visit_unit()
                     }
                 }
-                Lit::Null(_) => visitor.visit_none(),
+                Lit::Null(null) => {
+                    self.context.warn(|| WarningKind::NullAsNone, null.span);
+
+                    visitor.visit_none()
+                }
                 Lit::Str(_) => self.deserialize_str(visitor),
-                _ => Err(Self::Error::UnexpectedExpr(self.expr.into_owned())),
+                Lit::Regex(_) if self.context.options().regex_as_string => {
+                    self.deserialize_str(visitor)
+                }
+                _ => {
+                    self.recover(
+                        Err(Self::Error::UnexpectedExpr(self.expr.as_ref().clone())),
+                        || (),
+                    )?;
+
+                    visitor.visit_unit()
+                }
             },
-            Expr::Ident(_) => self.deserialize_str(visitor),
-            other => Err(Self::Error::UnexpectedExpr(other.clone())),
+            Expr::Ident(ident) => match ident.sym.as_str() {
+                "undefined" => visitor.visit_none(),
+                "NaN" => visitor.visit_f64(f64::NAN),
+                "Infinity" => visitor.visit_f64(f64::INFINITY),
+                _ => self.deserialize_str(visitor),
+            },
+            Expr::Call(_) | Expr::New(_) if regex::from_expr(&self.expr).is_some() => {
+                if self.context.options().regex_as_string {
+                    self.deserialize_str(visitor)
+                } else {
+                    self.recover(
+                        Err(Self::Error::UnexpectedExpr(self.expr.as_ref().clone())),
+                        || (),
+                    )?;
+
+                    visitor.visit_unit()
+                }
+            }
+            Expr::Call(_) if let Some(decoded) = builtin::decode(&self.expr, &self.context) => {
+                visitor.visit_string(
+                    self.recover(decoded.map(builtin::Decoded::into_string), String::new)?,
+                )
+            }
+            Expr::Member(_) | Expr::Call(_) | Expr::New(_)
+                if self.context.options().expressions_as_variants =>
+            {
+                self.deserialize_variant(visitor)
+            }
+            other => {
+                self.recover(Err(Self::Error::UnexpectedExpr(other.clone())), || ())?;
+
+                visitor.visit_unit()
+            }
         }
     }
 
     fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         let expected = "boolean";
 
-        match &*self.expr {
-            Expr::Lit(Lit::Bool(value)) => visitor.visit_bool(value.value),
+        let value = match &*self.expr {
+            Expr::Lit(Lit::Bool(value)) => Ok(value.value),
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_bool(self.recover(value, bool::default)?)
     }
 
     fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
@@ -100,24 +373,40 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
     fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         let expected = "bytes";
 
+        self.consume_string()?;
+
         match self.expr {
             Cow::Borrowed(Expr::Lit(Lit::Str(str))) => {
                 visitor.visit_borrowed_bytes(str.value.as_bytes())
             }
             Cow::Owned(Expr::Lit(Lit::Str(str))) => visitor.visit_bytes(str.value.as_bytes()),
-            other => match &*other {
-                Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
-                Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
-                Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
-                other => Err(Self::Error::UnexpectedExpr(other.clone())),
-            },
+            ref other => {
+                let value = bytes::from_expr(other, &self.context)
+                    .or_else(|| {
+                        builtin::decode(other, &self.context)
+                            .map(|decoded| decoded.map(builtin::Decoded::into_bytes))
+                    })
+                    .unwrap_or_else(|| {
+                        Err(match &**other {
+                            Expr::Lit(lit) => Error::unexpected_lit(lit, expected),
+                            Expr::Object(_) => {
+                                Self::Error::invalid_type(Unexpected::Map, &expected)
+                            }
+                            other => Self::Error::UnexpectedExpr(other.clone()),
+                        })
+                    });
+
+                visitor.visit_byte_buf(self.recover(value, Vec::new)?)
+            }
         }
     }
 
     fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         let expected = "character";
 
-        match &*self.expr {
+        self.consume_string()?;
+
+        let value = match &*self.expr {
             Expr::Lit(Lit::Str(str)) => {
                 let mut chars = str.value.chars();
 
@@ -130,7 +419,7 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
                     },
                     |ch| {
                         if chars.next().is_none() {
-                            visitor.visit_char(ch)
+                            Ok(ch)
                         } else {
                             Err(Self::Error::invalid_value(
                                 Unexpected::Str(str.value.as_str()),
@@ -144,13 +433,15 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_char(self.recover(value, char::default)?)
     }
 
     fn deserialize_enum<V: Visitor<'de>>(
         self,
         _name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
         let expected = "enumeration";
@@ -173,7 +464,8 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
 
                                 visitor.visit_enum(Enum {
                                     key: Cow::Borrowed(key),
-                                    value: Cow::Borrowed(&kvp.value),
+                                    value: Payload::Value(Cow::Borrowed(&kvp.value)),
+                                    context: self.context.child(|| Segment::Key(key.to_string())),
                                 })
                             }
                             other => Err(Self::Error::UnexpectedProp(Box::new(other.clone()))),
@@ -197,7 +489,8 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
 
                                 visitor.visit_enum(Enum {
                                     key: Cow::Owned(key.to_string()),
-                                    value: Cow::Owned(*kvp.value),
+                                    value: Payload::Value(Cow::Owned(*kvp.value)),
+                                    context: self.context.child(|| Segment::Key(key.to_string())),
                                 })
                             }
                             other => Err(Self::Error::UnexpectedProp(Box::new(other))),
@@ -214,169 +507,361 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
                     Err(Self::Error::invalid_length(props.len(), &"1"))
                 }
             }
+            call @ (Cow::Borrowed(Expr::Call(_) | Expr::New(_))
+            | Cow::Owned(Expr::Call(_) | Expr::New(_))) => {
+                visitor.visit_enum(Enum::from_call(call, &self.context)?)
+            }
+            array @ (Cow::Borrowed(Expr::Array(_)) | Cow::Owned(Expr::Array(_)))
+                if self.context.options().array_tagged_enums =>
+            {
+                visitor.visit_enum(Enum::from_array(array, variants, &self.context)?)
+            }
             other => match &*other {
+                Expr::Lit(Lit::Num(number)) if self.context.options().enum_variant_index => {
+                    visitor.visit_enum(variant_at(number, variants)?.into_deserializer())
+                }
                 Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
                 Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
                 Expr::Ident(ident) => visitor.visit_enum(ident.sym.as_str().into_deserializer()),
+                Expr::Member(_) => visitor
+                    .visit_enum(variant_name(&other, self.context.options())?.into_deserializer()),
                 other => Err(Self::Error::UnexpectedExpr(other.clone())),
             },
         }
     }
 
     fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_f32(visitor);
+        }
+
         let expected = "f32";
 
-        match &*self.expr {
-            Expr::Lit(Lit::Num(number)) =>
-            {
+        let value = match &*self.expr {
+            Expr::Lit(Lit::Num(number)) => {
                 #[allow(clippy::cast_possible_truncation)]
-                visitor.visit_f32(number.value as f32)
+                let value = number.value as f32;
+                #[allow(clippy::float_cmp)]
+                let truncated = f64::from(value) != number.value && !number.value.is_nan();
+
+                if truncated {
+                    self.context
+                        .warn(|| WarningKind::TruncatedFloat(number.value), number.span);
+                }
+
+                Ok(value)
             }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_f32(self.recover(value, f32::default)?)
     }
 
     fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_f64(visitor);
+        }
+
         let expected = "f64";
 
-        match &*self.expr {
-            Expr::Lit(Lit::Num(number)) => visitor.visit_f64(number.value),
+        let value = match &*self.expr {
+            Expr::Lit(Lit::Num(number)) => Ok(number.value),
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_f64(self.recover(value, f64::default)?)
     }
 
     fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_i128(visitor);
+        }
+
         let expected = "i128";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_i128(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_i128(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_i128(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
-            other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+            other => date::from_expr(other).map_or_else(
+                || Err(Self::Error::UnexpectedExpr(other.clone())),
+                |date| date.map(|date| i128::from(date.timestamp_millis())),
+            ),
+        };
+
+        visitor.visit_i128(self.recover(value, i128::default)?)
     }
 
     fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_i16(visitor);
+        }
+
         let expected = "i16";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_i16(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_i16(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_i16(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_i16(self.recover(value, i16::default)?)
     }
 
     fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_i32(visitor);
+        }
+
         let expected = "i32";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_i32(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_i32(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_i32(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_i32(self.recover(value, i32::default)?)
     }
 
     fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_i64(visitor);
+        }
+
         let expected = "i64";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_i64(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_i64(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_i64(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
-            other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+            other => date::from_expr(other).map_or_else(
+                || Err(Self::Error::UnexpectedExpr(other.clone())),
+                |date| date.map(Date::timestamp_millis),
+            ),
+        };
+
+        visitor.visit_i64(self.recover(value, i64::default)?)
     }
 
     fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_i8(visitor);
+        }
+
         let expected = "i8";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_i8(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_i8(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_i8(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_i8(self.recover(value, i8::default)?)
     }
 
     fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        self.deserialize_str(visitor)
+        // Internally and adjacently tagged enums read their tags as identifiers, which may be
+        // variant indices.
+        match &*self.expr {
+            Expr::Lit(Lit::Num(number)) if let Some(index) = number::number_to_u64(number) => {
+                visitor.visit_u64(index)
+            }
+            _ => self.deserialize_str(visitor),
+        }
     }
 
     fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
-        self.deserialize_any(visitor)
+        // Ignored values are never inspected, so they may contain any kind of expression.
+        self.context.ignore();
+
+        visitor.visit_unit()
     }
 
     fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         match self.expr {
             Cow::Borrowed(Expr::Object(ObjectLit { props, .. })) => {
-                visitor.visit_map(Map::new(Cow::Borrowed(props)))
+                self.context.enter(props.len())?;
+
+                visitor.visit_map(Map::new(Cow::Borrowed(props), self.context))
             }
             Cow::Owned(Expr::Object(ObjectLit { props, .. })) => {
-                visitor.visit_map(Map::new(Cow::Owned(props)))
+                self.context.enter(props.len())?;
+
+                visitor.visit_map(Map::new(Cow::Owned(props), self.context))
             }
-            other => Err(Self::Error::UnexpectedExpr(other.into_owned())),
+            other => match collection_elems(other, &["Map"]) {
+                Ok(entries) => {
+                    self.context.enter(entries.len())?;
+
+                    visitor.visit_map(Entries::new(entries, self.context))
+                }
+                Err(other) => Err(Self::Error::UnexpectedExpr(other.into_owned())),
+            },
         }
     }
 
     fn deserialize_newtype_struct<V: Visitor<'de>>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        visitor.visit_newtype_struct(self)
+        if name == raw::JS_NAME {
+            let span = self.expr.span();
+
+            self.context.source_text(span).map_or_else(
+                || Err(Self::Error::SourceUnavailable(span)),
+                |source| visitor.visit_borrowed_str(source),
+            )
+        } else if name == raw::EXPR_NAME {
+            let source = self.context.source();
+
+            raw::capture(self.expr, self.context);
+
+            match source {
+                Some(source) => visitor.visit_borrowed_str(source),
+                None => visitor.visit_unit(),
+            }
+        } else if name == regex::NAME {
+            let value = regex::from_expr(&self.expr).unwrap_or_else(|| {
+                Err(match &*self.expr {
+                    Expr::Lit(lit) => Error::unexpected_lit(lit, "regular expression"),
+                    other => Self::Error::UnexpectedExpr(other.clone()),
+                })
+            });
+
+            visitor.visit_string(self.recover(value, Regex::default)?.to_string())
+        } else if name == value::NAME {
+            // Other values are deserialized as they are for any self-describing type.
+            match value::literal(&self.expr, &self.context) {
+                Some(value) => visitor.visit_newtype_struct(value::Encoded(value?)),
+                None => visitor.visit_newtype_struct(self),
+            }
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
     }
 
     fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         match self.expr {
             Cow::Borrowed(Expr::Array(ArrayLit { elems, .. })) => {
-                visitor.visit_seq(Seq::new(Cow::Borrowed(elems)))
+                self.context.enter(elems.len())?;
+
+                visitor.visit_seq(Seq::new(Cow::Borrowed(elems), self.context))
             }
             Cow::Owned(Expr::Array(ArrayLit { elems, .. })) => {
-                visitor.visit_seq(Seq::new(Cow::Owned(elems)))
+                self.context.enter(elems.len())?;
+
+                visitor.visit_seq(Seq::new(Cow::Owned(elems), self.context))
             }
-            other => Err(Self::Error::UnexpectedExpr(other.into_owned())),
+            other => match collection_elems(other, &["Set"])
+                .or_else(|other| collection_elems(other, bytes::TYPED_ARRAYS))
+            {
+                Ok(elems) => {
+                    self.context.enter(elems.len())?;
+
+                    visitor.visit_seq(Seq::new(elems, self.context))
+                }
+                Err(other) => {
+                    self.context.recover(
+                        Err(Self::Error::UnexpectedExpr(other.as_ref().clone())),
+                        other.span(),
+                        || (),
+                    )?;
+
+                    visitor.visit_seq(Seq::<Option<ExprOrSpread>>::new(
+                        Cow::Owned(vec![]),
+                        self.context,
+                    ))
+                }
+            },
         }
     }
 
     fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         let expected = "string";
 
+        self.consume_string()?;
+
         match self.expr {
             Cow::Borrowed(Expr::Lit(Lit::Str(str))) => {
                 visitor.visit_borrowed_str(str.value.as_str())
             }
-            Cow::Owned(Expr::Lit(Lit::Str(str))) => visitor.visit_str(str.value.as_str()),
-            Cow::Borrowed(Expr::Ident(ident)) => visitor.visit_borrowed_str(ident.sym.as_str()),
-            Cow::Owned(Expr::Ident(ident)) => visitor.visit_str(ident.sym.as_str()),
-            other => match &*other {
-                Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
-                Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
-                Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
-                other => Err(Self::Error::UnexpectedExpr(other.clone())),
-            },
+            Cow::Owned(Expr::Lit(Lit::Str(str))) => {
+                match self.context.borrow_source(&str.value, str.span) {
+                    Some(value) => visitor.visit_borrowed_str(value),
+                    None => visitor.visit_str(str.value.as_str()),
+                }
+            }
+            Cow::Borrowed(Expr::Ident(ident)) => {
+                self.context.warn(
+                    || WarningKind::IdentifierAsString(ident.sym.to_string()),
+                    ident.span,
+                );
+
+                visitor.visit_borrowed_str(ident.sym.as_str())
+            }
+            Cow::Owned(Expr::Ident(ident)) => {
+                self.context.warn(
+                    || WarningKind::IdentifierAsString(ident.sym.to_string()),
+                    ident.span,
+                );
+
+                match self.context.borrow_source(&ident.sym, ident.span) {
+                    Some(name) => visitor.visit_borrowed_str(name),
+                    None => visitor.visit_str(ident.sym.as_str()),
+                }
+            }
+            ref other => {
+                if self.context.options().regex_as_string
+                    && let Some(value) = regex::from_expr(other)
+                {
+                    return visitor.visit_string(self.recover(value, Regex::default)?.to_string());
+                }
+
+                if let Some(value) = date::from_expr(other) {
+                    return visitor.visit_string(self.recover(value, Date::default)?.to_string());
+                }
+
+                if let Some(decoded) = builtin::decode(other, &self.context) {
+                    return visitor.visit_string(
+                        self.recover(decoded.map(builtin::Decoded::into_string), String::new)?,
+                    );
+                }
+
+                let error = match &**other {
+                    Expr::Lit(lit) => Error::unexpected_lit(lit, expected),
+                    Expr::Object(_) => Self::Error::invalid_type(Unexpected::Map, &expected),
+                    Expr::Array(_) => Self::Error::invalid_type(Unexpected::Seq, &expected),
+                    other => Self::Error::UnexpectedExpr(other.clone()),
+                };
+
+                visitor.visit_str(self.recover(Err(error), || "")?)
+            }
         }
     }
 
@@ -386,18 +871,38 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
 
     fn deserialize_struct<V: Visitor<'de>>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        self.deserialize_map(visitor)
+        if name == spanned::NAME && fields == spanned::FIELDS {
+            visitor.visit_map(spanned::SpannedMap::new(self))
+        } else {
+            self.deserialize_map(visitor)
+        }
     }
 
+    #[cfg_attr(not(feature = "time"), allow(unused_variables))]
     fn deserialize_tuple<V: Visitor<'de>>(
         self,
-        _len: usize,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
+        #[cfg(feature = "time")]
+        if len == date::TIME_COMPONENTS
+            && let Some(date) = date::from_expr(&self.expr)
+        {
+            let components = date.and_then(|date| {
+                date.time_components()
+                    .ok_or_else(|| Error::InvalidDate(self.expr.as_ref().clone()))
+            });
+
+            return visitor.visit_seq(serde::de::value::SeqDeserializer::new(
+                self.recover(components, <[i64; date::TIME_COMPONENTS]>::default)?
+                    .into_iter(),
+            ));
+        }
+
         self.deserialize_seq(visitor)
     }
 
@@ -411,85 +916,135 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
     }
 
     fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_u128(visitor);
+        }
+
         let expected = "u128";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_u128(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_u128(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_u128(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
-            other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+            other => date::from_expr(other).map_or_else(
+                || Err(Self::Error::UnexpectedExpr(other.clone())),
+                |date| {
+                    date.and_then(|date| {
+                        u128::try_from(date.timestamp_millis())
+                            .map_err(|_| Error::InvalidDate(other.clone()))
+                    })
+                },
+            ),
+        };
+
+        visitor.visit_u128(self.recover(value, u128::default)?)
     }
 
     fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_u16(visitor);
+        }
+
         let expected = "u16";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_u16(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_u16(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_u16(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_u16(self.recover(value, u16::default)?)
     }
 
     fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_u32(visitor);
+        }
+
         let expected = "u32";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_u32(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_u32(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_u32(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_u32(self.recover(value, u32::default)?)
     }
 
     fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_u64(visitor);
+        }
+
         let expected = "u64";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_u64(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_u64(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_u64(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
-            other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+            other => date::from_expr(other).map_or_else(
+                || Err(Self::Error::UnexpectedExpr(other.clone())),
+                |date| {
+                    date.and_then(|date| {
+                        u64::try_from(date.timestamp_millis())
+                            .map_err(|_| Error::InvalidDate(other.clone()))
+                    })
+                },
+            ),
+        };
+
+        visitor.visit_u64(self.recover(value, u64::default)?)
     }
 
     fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if let Some(number) = self.signed_number() {
+            return number.deserialize_u8(visitor);
+        }
+
         let expected = "u8";
 
-        match &*self.expr {
-            Expr::Lit(lit @ Lit::Num(number)) => number::number_to_u8(number)
-                .ok_or_else(|| Error::unexpected_lit(lit, expected))
-                .and_then(|value| visitor.visit_u8(value)),
+        let value = match &*self.expr {
+            Expr::Lit(lit @ Lit::Num(number)) => {
+                number::number_to_u8(number).ok_or_else(|| Error::unexpected_lit(lit, expected))
+            }
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        visitor.visit_u8(self.recover(value, u8::default)?)
     }
 
     fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         let expected = "null";
 
-        match &*self.expr {
-            Expr::Lit(Lit::Null(_)) => visitor.visit_unit(),
+        let value = match &*self.expr {
+            Expr::Lit(Lit::Null(_)) => Ok(()),
             Expr::Lit(lit) => Err(Error::unexpected_lit(lit, expected)),
             Expr::Object(_) => Err(Self::Error::invalid_type(Unexpected::Map, &expected)),
             Expr::Array(_) => Err(Self::Error::invalid_type(Unexpected::Seq, &expected)),
             other => Err(Self::Error::UnexpectedExpr(other.clone())),
-        }
+        };
+
+        self.recover(value, || ())?;
+
+        visitor.visit_unit()
     }
 
     fn deserialize_unit_struct<V: Visitor<'de>>(
@@ -503,24 +1058,213 @@ impl<'de> serde::de::Deserializer<'de> for Deserializer<'de> {
     fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
         match &*self.expr {
             Expr::Lit(Lit::Null(_)) => visitor.visit_none(),
+            Expr::Ident(ident) if ident.sym.as_str() == "undefined" => visitor.visit_none(),
             _ => visitor.visit_some(self),
         }
     }
 }
 
-fn prop_name_to_str(prop_name: &PropName) -> Option<&str> {
-    prop_name
-        .as_str()
-        .map(|str| str.value.as_str())
+/// The variant named by an identifier or by the property of a member expression.
+///
+/// The object of a member expression is checked against the configured enum prefix.
+fn variant_name<'a>(expr: &'a Expr, options: &Options) -> Result<&'a str, Error> {
+    match expr {
+        Expr::Ident(ident) => Ok(ident.sym.as_str()),
+        Expr::Member(member) => {
+            let (object, variant) =
+                member_variant(member).ok_or_else(|| Error::UnexpectedExpr(expr.clone()))?;
+
+            if let Some(prefix) = &options.enum_prefix
+                && !(object == *prefix
+                    || object
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with('.')))
+            {
+                return Err(Error::UnexpectedEnumObject(object));
+            }
+
+            Ok(variant)
+        }
+        other => Err(Error::UnexpectedExpr(other.clone())),
+    }
+}
+
+/// The variant named by the tag of an array-tagged enum.
+fn variant_tag<'a>(
+    tag: &'a ExprOrSpread,
+    variants: &'static [&'static str],
+    context: &Context<'_>,
+) -> Result<&'a str, Error> {
+    match tag {
+        ExprOrSpread { spread: None, expr } => match &**expr {
+            Expr::Lit(Lit::Str(str)) => Ok(str.value.as_str()),
+            Expr::Lit(Lit::Num(number)) if context.options().enum_variant_index => {
+                variant_at(number, variants)
+            }
+            Expr::Lit(lit) => Err(Error::unexpected_lit(lit, "variant")),
+            other => Err(Error::UnexpectedExpr(other.clone())),
+        },
+        other => Err(Error::InvalidArrayElement(Some(other.clone()))),
+    }
+}
+
+/// The variant at a position in the list of variants.
+fn variant_at(
+    index: &swc_ecma_ast::Number,
+    variants: &'static [&'static str],
+) -> Result<&'static str, Error> {
+    let expected = "variant index";
+
+    number::number_to_u64(index)
+        .and_then(|index| usize::try_from(index).ok())
+        .and_then(|index| variants.get(index).copied())
+        .ok_or_else(|| {
+            number::number_to_unexpected(index).map_or_else(
+                || Error::InvalidNumber(index.clone()),
+                |unexpected| Error::invalid_value(unexpected, &expected),
+            )
+        })
+}
+
+/// Split a member expression such as `Color.Red` or `Api.Color["Red"]` into the dotted name of
+/// the object and the name of the property.
+fn member_variant(member: &MemberExpr) -> Option<(String, &str)> {
+    fn object_name(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Ident(ident) => Some(ident.sym.to_string()),
+            Expr::Member(member) => {
+                member_variant(member).map(|(object, name)| format!("{object}.{name}"))
+            }
+            _ => None,
+        }
+    }
+
+    let name = match &member.prop {
+        MemberProp::Ident(ident) => ident.sym.as_str(),
+        MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+            Expr::Lit(Lit::Str(str)) => str.value.as_str(),
+            _ => return None,
+        },
+        MemberProp::PrivateName(_) => return None,
+    };
+
+    object_name(&member.obj).map(|object| (object, name))
+}
+
+/// Whether the expression constructs one of the named globals, as in `new Map(...)`.
+fn is_new(expr: &Expr, names: &[&str]) -> bool {
+    expr.as_new().is_some_and(|new| {
+        new.callee
+            .as_ident()
+            .is_some_and(|ident| names.contains(&ident.sym.as_str()))
+    })
+}
+
+/// The elements of the array passed to a collection constructor such as `new Set([...])`.
+///
+/// The argument may be omitted. Any other expression is returned unchanged.
+fn collection_elems<'de>(
+    expr: Cow<'de, Expr>,
+    names: &[&str],
+) -> Result<Cow<'de, [Option<ExprOrSpread>]>, Cow<'de, Expr>> {
+    if !is_new(&expr, names) {
+        return Err(expr);
+    }
+
+    match expr {
+        Cow::Borrowed(Expr::New(NewExpr { args, .. })) => match args.as_deref() {
+            None | Some([]) => Ok(Cow::Owned(vec![])),
+            Some(
+                [
+                    ExprOrSpread {
+                        spread: None,
+                        expr: arg,
+                    },
+                ],
+            ) => match &**arg {
+                Expr::Array(ArrayLit { elems, .. }) => Ok(Cow::Borrowed(elems)),
+                _ => Err(expr),
+            },
+            _ => Err(expr),
+        },
+        Cow::Owned(Expr::New(mut new)) => match new.args.as_deref_mut() {
+            None | Some([]) => Ok(Cow::Owned(vec![])),
+            Some(
+                [
+                    ExprOrSpread {
+                        spread: None,
+                        expr: arg,
+                    },
+                ],
+            ) => match &mut **arg {
+                Expr::Array(ArrayLit { elems, .. }) => Ok(Cow::Owned(std::mem::take(elems))),
+                _ => Err(Cow::Owned(Expr::New(new))),
+            },
+            _ => Err(Cow::Owned(Expr::New(new))),
+        },
+        other => Err(other),
+    }
+}
+
+fn prop_name_to_str(prop_name: &PropName) -> Option<&str> {
+    prop_name
+        .as_str()
+        .map(|str| str.value.as_str())
         .or_else(|| prop_name.as_ident().map(|ident| ident.sym.as_str()))
 }
 
-struct Seq<'de> {
-    values: Cow<'de, [Option<ExprOrSpread>]>,
+/// The key of a property of an object literal, where a numeric key is the number as a string.
+fn prop_name_to_key(prop_name: &PropName) -> Option<Cow<'_, str>> {
+    prop_name_to_str(prop_name).map(Cow::Borrowed).or_else(|| {
+        prop_name
+            .as_num()
+            .map(|number| Cow::Owned(number::to_js_string(number.value)))
+    })
+}
+
+/// An element of an array literal, which may be a hole, or an argument of a call.
+trait Element: Clone {
+    fn is_hole(&self) -> bool {
+        false
+    }
+
+    fn as_expr_or_spread(&self) -> Result<&ExprOrSpread, Error>;
+    fn into_expr_or_spread(self) -> Result<ExprOrSpread, Error>;
+}
+
+impl Element for Option<ExprOrSpread> {
+    fn is_hole(&self) -> bool {
+        self.is_none()
+    }
+
+    fn as_expr_or_spread(&self) -> Result<&ExprOrSpread, Error> {
+        self.as_ref()
+            .ok_or_else(|| Error::InvalidArrayElement(self.clone()))
+    }
+
+    fn into_expr_or_spread(self) -> Result<ExprOrSpread, Error> {
+        self.ok_or(Error::InvalidArrayElement(None))
+    }
+}
+
+impl Element for ExprOrSpread {
+    fn as_expr_or_spread(&self) -> Result<&ExprOrSpread, Error> {
+        Ok(self)
+    }
+
+    fn into_expr_or_spread(self) -> Result<ExprOrSpread, Error> {
+        Ok(self)
+    }
+}
+
+struct Seq<'de, E: Element = Option<ExprOrSpread>> {
+    values: Cow<'de, [E]>,
+    index: usize,
+    context: Context<'de>,
 }
 
-impl<'de> Seq<'de> {
-    fn new(values: Cow<'de, [Option<ExprOrSpread>]>) -> Self {
+impl<'de, E: Element> Seq<'de, E> {
+    fn new(values: Cow<'de, [E]>, context: Context<'de>) -> Self {
         Self {
             values: match values {
                 Cow::Borrowed(values) => Cow::Borrowed(values),
@@ -530,11 +1274,20 @@ impl<'de> Seq<'de> {
                     Cow::Owned(values)
                 }
             },
+            index: 0,
+            context,
         }
     }
+
+    fn next_context(&mut self) -> Context<'de> {
+        let index = self.index;
+        self.index += 1;
+
+        self.context.child(|| Segment::Index(index))
+    }
 }
 
-impl<'de> SeqAccess<'de> for Seq<'de> {
+impl<'de, E: Element> SeqAccess<'de> for Seq<'de, E> {
     type Error = Error;
 
     fn next_element_seed<T: serde::de::DeserializeSeed<'de>>(
@@ -545,31 +1298,44 @@ impl<'de> SeqAccess<'de> for Seq<'de> {
             Cow::Borrowed(values) => {
                 if values.is_empty() {
                     Ok(None)
-                } else {
-                    let value = &values[0];
+                } else if values[0].is_hole() {
+                    self.values = Cow::Borrowed(&values[1..]);
+                    self.next_context();
 
-                    let expr_or_spread = value
-                        .as_ref()
-                        .ok_or_else(|| Error::InvalidArrayElement(value.clone()))?;
+                    seed.deserialize(value::Hole).map(Some)
+                } else {
+                    let expr_or_spread = values[0].as_expr_or_spread()?;
 
                     self.values = Cow::Borrowed(&values[1..]);
 
-                    seed.deserialize(Deserializer {
-                        expr: Cow::Borrowed(&expr_or_spread.expr),
-                    })
-                    .map(Some)
+                    let context = self.next_context();
+                    let span = expr_or_spread.expr.span();
+
+                    Deserializer::resolved(Cow::Borrowed(&expr_or_spread.expr), context.clone())
+                        .and_then(|deserializer| seed.deserialize(deserializer))
+                        .map(Some)
+                        .map_err(|error| context.locate(error, span))
                 }
             }
-            Cow::Owned(values) => values
-                .pop()
-                .map(|value| {
-                    let expr_or_spread = value.ok_or_else(|| Error::InvalidArrayElement(None))?;
+            Cow::Owned(values) => match values.pop() {
+                Some(value) if value.is_hole() => {
+                    self.next_context();
 
-                    seed.deserialize(Deserializer {
-                        expr: Cow::Owned(*expr_or_spread.expr),
-                    })
-                })
-                .map_or(Ok(None), |value| value.map(Some)),
+                    seed.deserialize(value::Hole).map(Some)
+                }
+                Some(value) => {
+                    let expr_or_spread = value.into_expr_or_spread()?;
+
+                    let context = self.next_context();
+                    let span = expr_or_spread.expr.span();
+
+                    Deserializer::resolved(Cow::Owned(*expr_or_spread.expr), context.clone())
+                        .and_then(|deserializer| seed.deserialize(deserializer))
+                        .map(Some)
+                        .map_err(|error| context.locate(error, span))
+                }
+                None => Ok(None),
+            },
         }
     }
 
@@ -580,11 +1346,12 @@ impl<'de> SeqAccess<'de> for Seq<'de> {
 
 struct Map<'de> {
     fields: Cow<'de, [PropOrSpread]>,
-    value: Option<Cow<'de, Expr>>,
+    value: Option<(Cow<'de, Expr>, Context<'de>)>,
+    context: Context<'de>,
 }
 
 impl<'de> Map<'de> {
-    fn new(fields: Cow<'de, [PropOrSpread]>) -> Self {
+    fn new(fields: Cow<'de, [PropOrSpread]>, context: Context<'de>) -> Self {
         Self {
             fields: match fields {
                 Cow::Borrowed(fields) => Cow::Borrowed(fields),
@@ -595,6 +1362,7 @@ impl<'de> Map<'de> {
                 }
             },
             value: None,
+            context,
         }
     }
 }
@@ -606,52 +1374,78 @@ impl<'de> MapAccess<'de> for Map<'de> {
         &mut self,
         seed: K,
     ) -> Result<Option<K::Value>, Self::Error> {
-        match &mut self.fields {
+        let (key, key_span, value) = match &mut self.fields {
             Cow::Borrowed(fields) => {
-                if fields.is_empty() {
-                    Ok(None)
-                } else {
-                    let prop_or_spread = &fields[0];
+                let current: &'de [PropOrSpread] = fields;
 
-                    match prop_or_spread {
-                        PropOrSpread::Prop(prop) => match &**prop {
-                            Prop::KeyValue(kvp) => {
-                                self.value = Some(Cow::Borrowed(&kvp.value));
-
-                                let key_str = prop_name_to_str(&kvp.key).ok_or_else(|| {
-                                    Error::UnexpectedProp(Box::new(*prop.clone()))
-                                })?;
+                let Some((prop_or_spread, rest)) = current.split_first() else {
+                    return Ok(None);
+                };
 
-                                self.fields = Cow::Borrowed(&fields[1..]);
+                *fields = rest;
 
-                                seed.deserialize(key_str.into_deserializer()).map(Some)
-                            }
-                            other => Err(Error::UnexpectedProp(Box::new(other.clone()))),
-                        },
-                        PropOrSpread::Spread(spread) => {
-                            Err(Error::UnexpectedSpread(spread.clone()))
+                match prop_or_spread {
+                    PropOrSpread::Prop(prop) => {
+                        match &**prop {
+                            Prop::KeyValue(kvp) => (
+                                prop_name_to_key(&kvp.key).ok_or_else(|| {
+                                    Error::UnexpectedProp(Box::new(*prop.clone()))
+                                })?,
+                                kvp.key.span(),
+                                Cow::Borrowed(&*kvp.value),
+                            ),
+                            // A shorthand property is the value of the identifier with the same name.
+                            Prop::Shorthand(ident) => (
+                                Cow::Borrowed(ident.sym.as_str()),
+                                ident.span,
+                                Cow::Owned(Expr::Ident(ident.clone())),
+                            ),
+                            other => return Err(Error::UnexpectedProp(Box::new(other.clone()))),
                         }
                     }
+                    PropOrSpread::Spread(spread) => {
+                        return Err(Error::UnexpectedSpread(spread.clone()));
+                    }
                 }
             }
-            Cow::Owned(fields) => fields
-                .pop()
-                .map(|prop_or_spread| match prop_or_spread {
-                    PropOrSpread::Prop(prop) => match *prop {
-                        Prop::KeyValue(kvp) => {
-                            self.value = Some(Cow::Owned(*kvp.value));
+            Cow::Owned(fields) => match fields.pop() {
+                Some(PropOrSpread::Prop(prop)) => match *prop {
+                    Prop::KeyValue(kvp) => (
+                        Cow::Owned(
+                            prop_name_to_key(&kvp.key)
+                                .ok_or_else(|| Error::InvalidObjectKey(kvp.key.clone()))?
+                                .into_owned(),
+                        ),
+                        kvp.key.span(),
+                        Cow::Owned(*kvp.value),
+                    ),
+                    Prop::Shorthand(ident) => (
+                        Cow::Owned(ident.sym.to_string()),
+                        ident.span,
+                        Cow::Owned(Expr::Ident(ident)),
+                    ),
+                    other => return Err(Error::UnexpectedProp(Box::new(other))),
+                },
+                Some(PropOrSpread::Spread(spread)) => return Err(Error::UnexpectedSpread(spread)),
+                None => return Ok(None),
+            },
+        };
 
-                            let key_str = prop_name_to_str(&kvp.key)
-                                .ok_or_else(|| Error::InvalidObjectKey(kvp.key.clone()))?;
+        self.context.consume_string(key.len())?;
 
-                            seed.deserialize(key_str.into_deserializer())
-                        }
-                        other => Err(Error::UnexpectedProp(Box::new(other))),
-                    },
-                    PropOrSpread::Spread(spread) => Err(Error::UnexpectedSpread(spread)),
-                })
-                .map_or(Ok(None), |value| value.map(Some)),
+        let context = self.context.child(|| Segment::Key(key.to_string()));
+
+        self.value = Some((value, context.clone()));
+
+        match key {
+            Cow::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+            Cow::Owned(key) => match self.context.borrow_source(&key, key_span) {
+                Some(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+                None => seed.deserialize(key.into_deserializer()),
+            },
         }
+        .map(Some)
+        .map_err(|error| context.locate(error, key_span))
     }
 
     fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(
@@ -660,7 +1454,13 @@ impl<'de> MapAccess<'de> for Map<'de> {
     ) -> Result<V::Value, Self::Error> {
         self.value.take().map_or_else(
             || Err(Error::ExpectedFieldValue),
-            |value| seed.deserialize(Deserializer { expr: value }),
+            |(value, context)| {
+                let span = value.span();
+
+                Deserializer::resolved(value, context.clone())
+                    .and_then(|deserializer| seed.deserialize(deserializer))
+                    .map_err(|error| context.locate(error, span))
+            },
         )
     }
 
@@ -669,9 +1469,252 @@ impl<'de> MapAccess<'de> for Map<'de> {
     }
 }
 
+/// The entries of a `new Map([[key, value], ...])` expression, whose keys may be any expression.
+struct Entries<'de> {
+    values: Cow<'de, [Option<ExprOrSpread>]>,
+    index: usize,
+    value: Option<(Cow<'de, Expr>, Context<'de>)>,
+    context: Context<'de>,
+}
+
+impl<'de> Entries<'de> {
+    const fn new(entries: Cow<'de, [Option<ExprOrSpread>]>, context: Context<'de>) -> Self {
+        Self {
+            values: entries,
+            index: 0,
+            value: None,
+            context,
+        }
+    }
+}
+
+/// Split a `[key, value]` entry.
+fn entry_parts(entry: Option<&ExprOrSpread>) -> Result<(&Expr, &Expr), Error> {
+    match entry {
+        Some(ExprOrSpread { spread: None, expr }) => match &**expr {
+            Expr::Array(ArrayLit { elems, .. }) => match elems.as_slice() {
+                [
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: key,
+                    }),
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: value,
+                    }),
+                ] => Ok((key, value)),
+                _ => Err(Error::UnexpectedExpr((**expr).clone())),
+            },
+            other => Err(Error::UnexpectedExpr(other.clone())),
+        },
+        other => Err(Error::InvalidArrayElement(other.cloned())),
+    }
+}
+
+fn into_entry_parts(entry: Option<ExprOrSpread>) -> Result<(Expr, Expr), Error> {
+    entry_parts(entry.as_ref())?;
+
+    // The entry has been checked to be an array with two elements.
+    match entry.map(|entry| *entry.expr) {
+        Some(Expr::Array(ArrayLit { elems, .. })) => {
+            let mut parts = elems.into_iter().flatten().map(|part| *part.expr);
+
+            parts
+                .next()
+                .zip(parts.next())
+                .ok_or(Error::ExpectedFieldValue)
+        }
+        _ => Err(Error::ExpectedFieldValue),
+    }
+}
+
+impl<'de> MapAccess<'de> for Entries<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let index = self.index;
+
+        let (key, value) = match &mut self.values {
+            Cow::Borrowed(entries) => {
+                let entries: &'de [Option<ExprOrSpread>] = entries;
+
+                match entries.get(index) {
+                    Some(entry) => {
+                        let (key, value) = entry_parts(entry.as_ref())?;
+
+                        (Cow::Borrowed(key), Cow::Borrowed(value))
+                    }
+                    None => return Ok(None),
+                }
+            }
+            Cow::Owned(entries) => match entries.get_mut(index) {
+                Some(entry) => {
+                    let (key, value) = into_entry_parts(entry.take())?;
+
+                    (Cow::Owned(key), Cow::Owned(value))
+                }
+                None => return Ok(None),
+            },
+        };
+
+        self.index += 1;
+
+        let context = self.context.child(|| match &*key {
+            Expr::Lit(Lit::Str(str)) => Segment::Key(str.value.to_string()),
+            _ => Segment::Index(index),
+        });
+        let span = key.span();
+
+        self.value = Some((value, context.clone()));
+
+        Deserializer::resolved(key, context.clone())
+            .and_then(|deserializer| seed.deserialize(deserializer))
+            .map(Some)
+            .map_err(|error| context.locate(error, span))
+    }
+
+    fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value.take().map_or_else(
+            || Err(Error::ExpectedFieldValue),
+            |(value, context)| {
+                let span = value.span();
+
+                Deserializer::resolved(value, context.clone())
+                    .and_then(|deserializer| seed.deserialize(deserializer))
+                    .map_err(|error| context.locate(error, span))
+            },
+        )
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len() - self.index)
+    }
+}
+
 struct Enum<'de> {
     key: Cow<'de, str>,
-    value: Cow<'de, Expr>,
+    value: Payload<'de>,
+    context: Context<'de>,
+}
+
+/// The contents of an enum variant.
+enum Payload<'de> {
+    /// The value of a single-key object such as `{ Variant: value }`.
+    Value(Cow<'de, Expr>),
+    /// The arguments of a call such as `Variant(a, b)`.
+    Args(Cow<'de, [ExprOrSpread]>),
+    /// The elements following the tag of an array such as `["Variant", a, b]`.
+    Elems(Cow<'de, [Option<ExprOrSpread>]>),
+}
+
+impl<'de> Payload<'de> {
+    /// The value of a newtype or struct variant, which must be given as a single argument or
+    /// element.
+    fn into_value(self) -> Result<Cow<'de, Expr>, Error> {
+        fn single<E: Element>(values: Cow<'_, [E]>) -> Result<Cow<'_, Expr>, Error> {
+            let expr_or_spread = match values {
+                Cow::Borrowed([value]) => Cow::Borrowed(value.as_expr_or_spread()?),
+                Cow::Owned(mut values) if values.len() == 1 => match values.pop() {
+                    Some(value) => Cow::Owned(value.into_expr_or_spread()?),
+                    None => return Err(Error::ExpectedFieldValue),
+                },
+                values => return Err(Error::invalid_length(values.len(), &"1 value")),
+            };
+
+            match expr_or_spread {
+                Cow::Borrowed(ExprOrSpread { spread: None, expr }) => Ok(Cow::Borrowed(expr)),
+                Cow::Owned(ExprOrSpread { spread: None, expr }) => Ok(Cow::Owned(*expr)),
+                other => Err(Error::InvalidArrayElement(Some(other.into_owned()))),
+            }
+        }
+
+        match self {
+            Self::Value(value) => Ok(value),
+            Self::Args(args) => single(args),
+            Self::Elems(elems) => single(elems),
+        }
+    }
+}
+
+impl<'de> Enum<'de> {
+    /// A variant written as an array whose first element is the tag, such as `["Variant", a, b]`.
+    fn from_array(
+        expr: Cow<'de, Expr>,
+        variants: &'static [&'static str],
+        context: &Context<'de>,
+    ) -> Result<Self, Error> {
+        let (key, elems) = match expr {
+            Cow::Borrowed(Expr::Array(ArrayLit { elems, .. })) => match elems.split_first() {
+                Some((tag, elems)) => (
+                    Cow::Borrowed(variant_tag(tag.as_expr_or_spread()?, variants, context)?),
+                    Cow::Borrowed(elems),
+                ),
+                None => return Err(Error::invalid_length(0, &"a tag")),
+            },
+            Cow::Owned(Expr::Array(ArrayLit { mut elems, .. })) => {
+                if elems.is_empty() {
+                    return Err(Error::invalid_length(0, &"a tag"));
+                }
+
+                let tag = elems.remove(0).into_expr_or_spread()?;
+
+                (
+                    Cow::Owned(variant_tag(&tag, variants, context)?.to_string()),
+                    Cow::Owned(elems),
+                )
+            }
+            other => return Err(Error::UnexpectedExpr(other.into_owned())),
+        };
+
+        Ok(Self {
+            context: context.child(|| Segment::Key(key.to_string())),
+            key,
+            value: Payload::Elems(elems),
+        })
+    }
+
+    /// A variant written as a call such as `Variant(a, b)` or `new Variant(a, b)`.
+    fn from_call(expr: Cow<'de, Expr>, context: &Context<'de>) -> Result<Self, Error> {
+        let (key, args) = match expr {
+            Cow::Borrowed(Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            })) => (
+                Cow::Borrowed(variant_name(callee, context.options())?),
+                Cow::Borrowed(args.as_slice()),
+            ),
+            Cow::Borrowed(Expr::New(NewExpr { callee, args, .. })) => (
+                Cow::Borrowed(variant_name(callee, context.options())?),
+                Cow::Borrowed(args.as_deref().unwrap_or_default()),
+            ),
+            Cow::Owned(Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            })) => (
+                Cow::Owned(variant_name(&callee, context.options())?.to_string()),
+                Cow::Owned(args),
+            ),
+            Cow::Owned(Expr::New(NewExpr { callee, args, .. })) => (
+                Cow::Owned(variant_name(&callee, context.options())?.to_string()),
+                Cow::Owned(args.unwrap_or_default()),
+            ),
+            other => return Err(Error::UnexpectedExpr(other.into_owned())),
+        };
+
+        Ok(Self {
+            context: context.child(|| Segment::Key(key.to_string())),
+            key,
+            value: Payload::Args(args),
+        })
+    }
 }
 
 impl<'de> EnumAccess<'de> for Enum<'de> {
@@ -692,14 +1735,29 @@ impl<'de> VariantAccess<'de> for Enum<'de> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
-        Err(Self::Error::UnexpectedExpr(self.value.into_owned()))
+        match self.value {
+            Payload::Value(value) => match &*value {
+                // Unit variants are often written as `{ Variant: null }` or `{ Variant: true }`.
+                Expr::Lit(Lit::Null(_) | Lit::Bool(Bool { value: true, .. })) => Ok(()),
+                Expr::Ident(ident) if ident.sym.as_str() == "undefined" => Ok(()),
+                Expr::Object(ObjectLit { props, .. }) if props.is_empty() => Ok(()),
+                _ => Err(Self::Error::UnexpectedExpr(value.into_owned())),
+            },
+            Payload::Args(args) if args.is_empty() => Ok(()),
+            Payload::Elems(elems) if elems.is_empty() => Ok(()),
+            Payload::Args(args) => Err(Self::Error::invalid_length(args.len(), &"no arguments")),
+            Payload::Elems(elems) => Err(Self::Error::invalid_length(elems.len(), &"no elements")),
+        }
     }
 
     fn newtype_variant_seed<T: serde::de::DeserializeSeed<'de>>(
         self,
         seed: T,
     ) -> Result<T::Value, Self::Error> {
-        seed.deserialize(Deserializer { expr: self.value })
+        seed.deserialize(Deserializer::resolved(
+            self.value.into_value()?,
+            self.context,
+        )?)
     }
 
     fn tuple_variant<V: Visitor<'de>>(
@@ -707,7 +1765,22 @@ impl<'de> VariantAccess<'de> for Enum<'de> {
         _len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        serde::de::Deserializer::deserialize_seq(Deserializer { expr: self.value }, visitor)
+        match self.value {
+            Payload::Value(value) => serde::de::Deserializer::deserialize_seq(
+                Deserializer::resolved(value, self.context)?,
+                visitor,
+            ),
+            Payload::Args(args) => {
+                self.context.enter(args.len())?;
+
+                visitor.visit_seq(Seq::new(args, self.context))
+            }
+            Payload::Elems(elems) => {
+                self.context.enter(elems.len())?;
+
+                visitor.visit_seq(Seq::new(elems, self.context))
+            }
+        }
     }
 
     fn struct_variant<V: Visitor<'de>>(
@@ -715,15 +1788,83 @@ impl<'de> VariantAccess<'de> for Enum<'de> {
         _fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error> {
-        serde::de::Deserializer::deserialize_map(Deserializer { expr: self.value }, visitor)
+        serde::de::Deserializer::deserialize_map(
+            Deserializer::resolved(self.value.into_value()?, self.context)?,
+            visitor,
+        )
+    }
+}
+
+/// An enum variant written as a call, seen by `deserialize_any` as a map with a single entry.
+struct VariantMap<'de> {
+    variant: Option<Enum<'de>>,
+    payload: Option<(Payload<'de>, Context<'de>)>,
+}
+
+impl<'de> VariantMap<'de> {
+    const fn new(variant: Enum<'de>) -> Self {
+        Self {
+            variant: Some(variant),
+            payload: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for VariantMap<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some(Enum {
+            key,
+            value,
+            context,
+        }) = self.variant.take()
+        else {
+            return Ok(None);
+        };
+
+        self.payload = Some((value, context));
+
+        match key {
+            Cow::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+            Cow::Owned(key) => seed.deserialize(key.into_deserializer()),
+        }
+        .map(Some)
+    }
+
+    fn next_value_seed<V: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (payload, context) = self.payload.take().ok_or(Error::ExpectedFieldValue)?;
+
+        // No arguments is a unit variant, one is a newtype or struct variant, and more are the
+        // fields of a tuple variant.
+        match payload {
+            Payload::Args(args) if args.is_empty() => seed.deserialize(UnitDeserializer::new()),
+            Payload::Args(args) if args.len() > 1 => {
+                context.enter(args.len())?;
+
+                seed.deserialize(SeqAccessDeserializer::new(Seq::new(args, context)))
+            }
+            payload => seed.deserialize(Deserializer::resolved(payload.into_value()?, context)?),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.variant.is_some()))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use super::warning::WarningKind;
     use swc_common::BytePos;
     use swc_ecma_ast::{EsVersion, Expr};
-    use swc_ecma_parser::{Parser, StringInput, Syntax, lexer::Lexer};
+    use swc_ecma_parser::{EsSyntax, Parser, StringInput, Syntax, lexer::Lexer};
 
     #[derive(Debug, thiserror::Error)]
     pub enum Error {
@@ -735,9 +1876,13 @@ mod test {
 
     pub fn parse_js(script: &str, version: EsVersion) -> Result<Box<Expr>, Error> {
         let lexer = Lexer::new(
-            Syntax::Es(Default::default()),
+            Syntax::Es(EsSyntax::default()),
             version,
-            StringInput::new(script, BytePos(0), BytePos(script.as_bytes().len() as u32)),
+            StringInput::new(
+                script,
+                BytePos(0),
+                BytePos(u32::try_from(script.len()).unwrap_or(u32::MAX)),
+            ),
             None,
         );
 
@@ -754,7 +1899,7 @@ mod test {
         let example_path = "../examples/google-play-chess.js";
         let script = parse_js(
             include_str!("../examples/google-play-chess.js"),
-            Default::default(),
+            EsVersion::default(),
         )?;
 
         match &*script {
@@ -763,7 +1908,7 @@ mod test {
 
                 let json = super::from_expr::<serde_json::Value>(object_lit_expr)?;
 
-                assert_eq!(json.as_object().map(|object| object.len()), Some(4));
+                assert_eq!(json.as_object().map(serde_json::Map::len), Some(4));
 
                 Ok(())
             }
@@ -806,7 +1951,7 @@ mod test {
 
         let expected_json_value = serde_json::from_str::<serde_json::Value>(JSON_STR).unwrap();
 
-        let script_js = parse_js(SCRIPT_STR, Default::default())?;
+        let script_js = parse_js(SCRIPT_STR, EsVersion::default())?;
 
         let test_value = super::from_expr::<TestStruct<'_>>(&script_js).unwrap();
 
@@ -846,4 +1991,956 @@ mod test {
 
         Ok(())
     }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct ValidatedStruct {
+        name: String,
+        enabled: bool,
+        sizes: Vec<u8>,
+        nested: Option<Box<Self>>,
+    }
+
+    #[test]
+    fn validate_collects_errors() -> Result<(), Error> {
+        let script_js = parse_js(
+            r#"{ name: 1, enabled: true, sizes: [1, "two", 3, 1000], nested: { name: "ok", enabled: null, sizes: {} } }"#,
+            EsVersion::default(),
+        )?;
+
+        let paths = super::validate::<ValidatedStruct>(&script_js)
+            .iter()
+            .map(|error| error.location().map(|(path, _)| path.to_string()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            paths,
+            vec![
+                Some("name".to_string()),
+                Some("sizes[1]".to_string()),
+                Some("sizes[3]".to_string()),
+                Some("nested.enabled".to_string()),
+                Some("nested.sizes".to_string()),
+            ]
+        );
+
+        let valid_js = parse_js(SCRIPT_STR, EsVersion::default())?;
+
+        assert!(super::validate::<TestStruct<'_>>(&valid_js).is_empty());
+
+        // Validation applies the same options as loading does.
+        let options = super::Options::new().global("SIZE", 2).max_length(3);
+
+        let sizes_js = parse_js("[SIZE, 1]", EsVersion::default())?;
+
+        assert_eq!(super::validate::<Vec<u8>>(&sizes_js).len(), 1);
+        assert!(options.validate_str::<Vec<u8>>("[SIZE, 1]").is_empty());
+        assert!(matches!(
+            options.validate_str::<Vec<u8>>("[1, 2, 3, 4]").as_slice(),
+            [super::Error::Located { source, .. }]
+                if matches!(**source, super::Error::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            options.validate_str::<Vec<u8>>("[1,").as_slice(),
+            [super::Error::EcmaParse(_)]
+        ));
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct LossyStruct {
+        ratio: f32,
+        exact: f32,
+        kind: String,
+        extra: serde_json::Value,
+    }
+
+    #[test]
+    fn warnings_for_lossy_conversions() -> Result<(), Error> {
+        let (value, warnings) = super::from_str_with_warnings::<LossyStruct>(
+            "{ ratio: 0.1, exact: 0.5, kind: primary, extra: [null] }",
+        )?;
+
+        assert_eq!(value.kind, "primary");
+
+        let kinds = warnings
+            .iter()
+            .map(|warning| (warning.path.to_string(), warning.kind.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("ratio".to_string(), WarningKind::TruncatedFloat(0.1)),
+                (
+                    "kind".to_string(),
+                    WarningKind::IdentifierAsString("primary".to_string())
+                ),
+                ("extra[0]".to_string(), WarningKind::NullAsNone),
+            ]
+        );
+
+        let options = super::Options::new()
+            .global("RATIO", 0.1)
+            .max_input_bytes(8);
+        let (ratio, warnings) = options.from_str_with_warnings::<f32>("RATIO")?;
+
+        assert!((ratio - 0.1).abs() < f32::EPSILON);
+        assert_eq!(warnings[0].kind, WarningKind::TruncatedFloat(0.1));
+        assert!(matches!(
+            options.from_str_with_warnings::<f32>("[RATIO][0]"),
+            Err(super::Error::LimitExceeded(_))
+        ));
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct PartialStruct {
+        name: String,
+        items: Vec<PartialItem>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct PartialItem {
+        id: u64,
+    }
+
+    #[test]
+    fn ignored_keys() -> Result<(), Error> {
+        let (value, ignored) = super::from_str_with_ignored::<PartialStruct>(
+            r#"{ name: "a", version: 2, items: [{ id: 1 }, { id: 2, "new field": { x: [1] } }] }"#,
+        )?;
+
+        assert_eq!(value.items.len(), 2);
+        assert_eq!(
+            ignored.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["version", r#"items[1]["new field"]"#]
+        );
+
+        let script = super::Script::parse(
+            r#"
+            var item = (function (id) { return { id, extra: 1 }; })(7);
+            module.exports = { name: "a", items: [item], version: 2 };
+            "#,
+        )?;
+
+        let (value, ignored) = script.exports_with_ignored::<PartialStruct>()?;
+
+        assert_eq!(value.items, vec![PartialItem { id: 7 }]);
+        assert_eq!(
+            ignored.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["items[0].extra", "version"]
+        );
+
+        let (_, ignored) = script.get_with_ignored::<PartialItem>("item")?;
+
+        assert_eq!(
+            ignored.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["extra"]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn ignored_unsupported_expressions() -> Result<(), Error> {
+        let value = super::from_str::<PartialItem>(
+            "{ onClick: function (e) { return e; }, id: 3, pattern: /a+/g, render: x => x * 2, at: new Date(), label: `${id}!`, Base: class {} }",
+        )?;
+
+        assert_eq!(value, PartialItem { id: 3 });
+
+        Ok(())
+    }
+
+    #[test]
+    fn limits() -> Result<(), Error> {
+        use super::options::{Limit, Options};
+
+        let nested = format!("{}1{}", "[".repeat(100_000), "]".repeat(100_000));
+
+        assert!(matches!(
+            Options::new()
+                .max_depth(64)
+                .from_str::<serde_json::Value>(&nested),
+            Err(super::Error::LimitExceeded(Limit::Depth(64)))
+        ));
+
+        // Nesting is limited by default, so that the parser cannot overflow the stack.
+        let default_limit = super::options::Limits::DEFAULT_MAX_DEPTH;
+
+        assert!(matches!(
+            super::from_str::<serde_json::Value>(&nested),
+            Err(super::Error::LimitExceeded(Limit::Depth(limit))) if limit == default_limit
+        ));
+
+        let nested = format!("{}1{}", "[".repeat(129), "]".repeat(129));
+
+        assert!(super::from_str::<serde_json::Value>(&nested).is_err());
+        assert!(
+            Options::new()
+                .max_depth(129)
+                .from_str::<serde_json::Value>(&nested)
+                .is_ok()
+        );
+        super::from_str::<serde::de::IgnoredAny>(&format!(
+            "[`{}`, '{}']",
+            "(".repeat(200),
+            "[".repeat(200)
+        ))?;
+
+        let nested = format!("{}1{}", "[".repeat(10), "]".repeat(10));
+        let nested_js = parse_js(&nested, EsVersion::default()).unwrap();
+
+        assert!(
+            Options::new()
+                .max_depth(10)
+                .from_expr::<serde_json::Value>(&nested_js)
+                .is_ok()
+        );
+        assert!(matches!(
+            Options::new()
+                .max_depth(9)
+                .from_expr::<serde_json::Value>(&nested_js),
+            Err(super::Error::LimitExceeded(Limit::Depth(9)))
+        ));
+
+        assert!(matches!(
+            Options::new()
+                .max_length(2)
+                .from_str::<Vec<u8>>("[1, 2, 3]"),
+            Err(super::Error::LimitExceeded(Limit::Length(2)))
+        ));
+
+        assert!(matches!(
+            Options::new()
+                .max_string_bytes(8)
+                .from_str::<Vec<String>>(r#"["abcd", "efgh", "i"]"#),
+            Err(super::Error::LimitExceeded(Limit::StringBytes(8)))
+        ));
+
+        assert!(matches!(
+            Options::new().max_input_bytes(4).from_str::<u64>("12345"),
+            Err(super::Error::LimitExceeded(Limit::InputBytes(4)))
+        ));
+
+        // Limits also apply to the other entry points that take options.
+        let program = swc_ecma_parser::Parser::new(
+            swc_ecma_parser::Syntax::Es(swc_ecma_parser::EsSyntax::default()),
+            swc_ecma_parser::StringInput::new(
+                "var sizes = [1, 2, 3];",
+                swc_common::BytePos(0),
+                swc_common::BytePos(22),
+            ),
+            None,
+        )
+        .parse_program()
+        .unwrap();
+
+        assert!(matches!(
+            super::Script::new(program)
+                .with_options(Options::new().max_length(2))
+                .get::<Vec<u8>>("sizes"),
+            Err(super::Error::LimitExceeded(Limit::Length(2)))
+        ));
+        assert!(matches!(
+            Options::new()
+                .max_depth(9)
+                .from_expr_with_ignored::<serde_json::Value>(&nested_js),
+            Err(super::Error::LimitExceeded(Limit::Depth(9)))
+        ));
+
+        Ok(())
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct SpannedStruct {
+        name: super::Spanned<String>,
+        ports: Vec<super::Spanned<u16>>,
+    }
+
+    #[test]
+    fn spanned() -> Result<(), Error> {
+        use super::spanned::LineColumn;
+
+        let source = "{\n  name: \"web\",\n  ports: [80, 8080]\n}";
+        let value = super::from_str::<SpannedStruct>(source)?;
+
+        assert_eq!(value.name.get_ref(), "web");
+        assert_eq!(&source[value.name.span()], "\"web\"");
+        assert_eq!(value.name.start(), Some(LineColumn { line: 2, column: 8 }));
+        assert_eq!(*value.ports[1].get_ref(), 8080);
+        assert_eq!(
+            value.ports[1].end(),
+            Some(LineColumn {
+                line: 3,
+                column: 18
+            })
+        );
+
+        let script_js = parse_js(source, EsVersion::default())?;
+        let value = super::from_expr::<SpannedStruct>(&script_js)?;
+
+        assert_eq!(&source[value.ports[0].span()], "80");
+        assert_eq!(value.ports[0].start(), None);
+
+        Ok(())
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct RawStruct<'a> {
+        name: String,
+        #[serde(borrow)]
+        validate: super::RawJs<'a>,
+        pattern: super::RawJs<'a>,
+    }
+
+    #[test]
+    fn raw_js() -> Result<(), Error> {
+        let source = r#"{ name: "id", validate: function (value) { return value > 0; }, pattern: /^[a-z]+$/i }"#;
+        let value = super::from_str::<RawStruct<'_>>(source)?;
+
+        assert_eq!(value.name, "id");
+        assert_eq!(
+            value.validate.as_str(),
+            "function (value) { return value > 0; }"
+        );
+        assert_eq!(value.pattern.as_str(), "/^[a-z]+$/i");
+
+        let script_js = parse_js(source, EsVersion::default())?;
+
+        assert!(matches!(
+            super::from_expr::<RawStruct<'_>>(&script_js),
+            Err(super::Error::SourceUnavailable(_))
+        ));
+
+        let value =
+            super::Options::new().from_expr_with_source::<RawStruct<'_>>(&script_js, source)?;
+
+        assert_eq!(value.pattern.as_str(), "/^[a-z]+$/i");
+
+        Ok(())
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Message<'a> {
+        kind: String,
+        #[serde(borrow)]
+        payload: super::RawExpr<'a>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Pair<'a> {
+        a: u32,
+        b: &'a str,
+    }
+
+    #[test]
+    fn raw_expr() -> Result<(), Error> {
+        let source = r#"[{ kind: "point", payload: { x: 1, y: 2 } }, { kind: "names", payload: ["a", "b"] }]"#;
+
+        let messages = super::from_str::<Vec<Message<'_>>>(source)?;
+        let script_js = parse_js(source, EsVersion::default())?;
+        let borrowed_messages = super::from_expr::<Vec<Message<'_>>>(&script_js)?;
+
+        assert_eq!(messages[0].payload.source_text(), Some("{ x: 1, y: 2 }"));
+        assert_eq!(borrowed_messages[0].payload.source_text(), None);
+        assert_eq!(
+            messages[1].payload.clone().into_value::<Vec<&str>>()?,
+            vec!["a", "b"]
+        );
+
+        for messages in [messages, borrowed_messages] {
+            let [point, names] = <[Message<'_>; 2]>::try_from(messages).unwrap();
+
+            assert_eq!(point.kind, "point");
+            assert_eq!(point.payload.into_value::<Point>()?, Point { x: 1, y: 2 });
+            assert_eq!(names.kind, "names");
+            assert_eq!(names.payload.into_value::<Vec<String>>()?, vec!["a", "b"]);
+        }
+
+        // Bindings of the script and of immediately invoked functions are still in scope.
+        let script = super::Script::parse(
+            "
+            var n = 42;
+            var v = { kind: 'num', payload: { a: n, b: 'x' } };
+            var w = (function (n) {
+                return { kind: 'list', payload: [n, 'y'] };
+            })(5);
+            ",
+        )?;
+        let v = script.get::<Message<'_>>("v")?;
+        let w = script.get::<Message<'_>>("w")?;
+
+        assert_eq!(v.payload.source_text(), Some("{ a: n, b: 'x' }"));
+        assert_eq!(v.payload.into_value::<Pair<'_>>()?, Pair { a: 42, b: "x" });
+        assert_eq!(
+            w.payload.into_value::<(u32, String)>()?,
+            (5, "y".to_string())
+        );
+
+        // So are the globals and other options.
+        let options = super::Options::new().global("n", 1).regex_as_string(true);
+
+        assert_eq!(
+            options
+                .from_str::<Message<'_>>("{ kind: 'n', payload: [n, n] }")?
+                .payload
+                .into_value::<Vec<u32>>()?,
+            vec![1, 1]
+        );
+
+        let regex = options.from_str::<Message<'_>>("{ kind: 'r', payload: /a/g }")?;
+
+        assert_eq!(regex.payload.clone().into_value::<String>()?, "/a/g");
+        assert!(
+            regex
+                .payload
+                .into_value_with::<String>(&super::Options::new())
+                .is_err()
+        );
+
+        let wrapped = super::from_str::<Option<Box<super::RawExpr<'_>>>>("'\\u0041\\n'")?;
+
+        assert_eq!(
+            wrapped.as_deref().and_then(super::RawExpr::source_text),
+            Some("'\\u0041\\n'")
+        );
+        assert_eq!(
+            wrapped.map(|raw| raw.into_value::<String>()).transpose()?,
+            Some("A\n".to_string())
+        );
+        assert!(serde_json::from_str::<super::RawExpr<'_>>("null").is_err());
+
+        Ok(())
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Validation {
+        pattern: super::Regex,
+        fallback: super::Regex,
+        plain: super::Regex,
+    }
+
+    #[test]
+    fn regex() -> Result<(), Error> {
+        let source = r#"{ pattern: /^[a-z]+\/\d$/gi, fallback: new RegExp("a/b", "m"), plain: RegExp(/x/g, "") }"#;
+        let value = super::from_str::<Validation>(source)?;
+
+        assert_eq!(value.pattern, super::Regex::new(r"^[a-z]+\/\d$", "gi"));
+        assert_eq!(value.fallback.to_string(), r"/a\/b/m");
+        assert_eq!(value.plain, super::Regex::new("x", ""));
+        assert_eq!(
+            serde_json::from_str::<super::Regex>(r#""/a+/g""#).unwrap(),
+            super::Regex::new("a+", "g")
+        );
+
+        assert!(super::from_str::<String>("/a+/g").is_err());
+        assert_eq!(
+            super::Options::new()
+                .regex_as_string(true)
+                .from_str::<Vec<String>>(r#"[/a+/g, new RegExp("")]"#)?,
+            vec!["/a+/g", "/(?:)/"]
+        );
+
+        #[cfg(feature = "regex")]
+        {
+            let regex = value.pattern.to_regex()?;
+
+            assert!(regex.is_match("AbC/1"));
+            assert!(!regex.is_match("abc/١"));
+            assert!(super::Regex::new("(?=a)", "").to_regex().is_err());
+            assert!(super::Regex::new("a", "y").to_regex().is_err());
+        }
+
+        Ok(())
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Event {
+        created: u64,
+        updated: String,
+        starts: super::Date,
+    }
+
+    #[test]
+    fn date() -> Result<(), Error> {
+        let source = r#"{ created: new Date(1700000000000), updated: new Date("2024-01-02T03:04:05+01:00"), starts: new Date(2024, 0, 31, 12) }"#;
+        let value = super::from_str::<Event>(source)?;
+
+        assert_eq!(value.created, 1_700_000_000_000);
+        assert_eq!(value.updated, "2024-01-02T02:04:05.000Z");
+        assert_eq!(value.starts.to_string(), "2024-01-31T12:00:00.000Z");
+
+        assert_eq!(
+            super::from_str::<Vec<String>>(
+                r#"[new Date("2024"), new Date("2024-02-29T23:59:59.9999Z"), new Date(99, 12), new Date(-1)]"#
+            )?,
+            vec![
+                "2024-01-01T00:00:00.000Z",
+                "2024-02-29T23:59:59.999Z",
+                "2000-01-01T00:00:00.000Z",
+                "1969-12-31T23:59:59.999Z"
+            ]
+        );
+        assert_eq!(
+            super::from_str::<String>("new Date(-8640000000000000)")?,
+            "-271821-04-20T00:00:00.000Z"
+        );
+
+        assert!(matches!(
+            super::from_str::<i64>(r#"new Date("2023-02-29")"#),
+            Err(super::Error::InvalidDate(_))
+        ));
+        assert!(matches!(
+            super::from_str::<i64>("new Date(8640000000000001)"),
+            Err(super::Error::InvalidDate(_))
+        ));
+        assert!(matches!(
+            super::from_str::<i64>("new Date()"),
+            Err(super::Error::UnexpectedExpr(_))
+        ));
+        assert_eq!(super::from_str::<i64>("new Date(-1)")?, -1);
+        assert_eq!(super::from_str::<i128>("new Date(1)")?, 1);
+        assert_eq!(super::from_str::<u128>("new Date(1)")?, 1);
+        assert!(matches!(
+            super::from_str::<u64>("new Date(-1)"),
+            Err(super::Error::InvalidDate(_))
+        ));
+
+        #[cfg(feature = "chrono")]
+        {
+            let value = super::from_str::<chrono::DateTime<chrono::Utc>>(
+                r#"new Date("2024-01-02T03:04:05Z")"#,
+            )?;
+
+            assert_eq!(Some(value), super::Date::from(value).to_chrono());
+        }
+
+        #[cfg(feature = "time")]
+        {
+            #[derive(serde::Deserialize)]
+            struct Timestamp {
+                #[serde(with = "time::serde::timestamp::milliseconds")]
+                at: time::OffsetDateTime,
+            }
+
+            assert_eq!(
+                value
+                    .starts
+                    .to_time()
+                    .map(time::OffsetDateTime::unix_timestamp),
+                Some(1_706_702_400)
+            );
+
+            let times = super::from_str::<Vec<time::OffsetDateTime>>(
+                r#"[new Date(1700000000123), new Date("2024-01-02T03:04:05+01:00")]"#,
+            )?;
+
+            assert_eq!(
+                times
+                    .iter()
+                    .map(|time| time.unix_timestamp_nanos() / 1_000_000)
+                    .collect::<Vec<_>>(),
+                [1_700_000_000_123, 1_704_161_045_000]
+            );
+            assert!(matches!(
+                super::from_str::<time::OffsetDateTime>("new Date(8640000000000000)"),
+                Err(super::Error::InvalidDate(_))
+            ));
+
+            assert_eq!(
+                super::from_str::<Timestamp>("{ at: new Date(1700000000123) }")?.at,
+                times[0]
+            );
+        }
+
+        Ok(())
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct AppState {
+        users: std::collections::HashMap<u64, String>,
+        flags: std::collections::BTreeMap<String, bool>,
+        tags: std::collections::BTreeSet<String>,
+        empty: Vec<u8>,
+    }
+
+    #[test]
+    fn map_and_set() -> Result<(), Error> {
+        let source = r#"{
+            users: new Map([[1, "alice"], [2, "bob"]]),
+            flags: new Map([["dark", true]]),
+            tags: new Set(["a", "b", "a"]),
+            empty: new Set(),
+        }"#;
+
+        let value = super::from_str::<AppState>(source)?;
+        let script_js = parse_js(source, EsVersion::default())?;
+        let borrowed_value = super::from_expr::<AppState>(&script_js)?;
+
+        for value in [value, borrowed_value] {
+            assert_eq!(value.users.get(&2).map(String::as_str), Some("bob"));
+            assert_eq!(value.flags.get("dark"), Some(&true));
+            assert_eq!(value.tags.len(), 2);
+            assert!(value.empty.is_empty());
+        }
+
+        assert_eq!(
+            super::from_str::<std::collections::BTreeMap<u8, u8>>("new Map()")?,
+            std::collections::BTreeMap::new()
+        );
+        assert_eq!(
+            super::from_str::<serde_json::Value>(r#"new Map([["a", new Set([1])]])"#)?,
+            serde_json::json!({ "a": [1] })
+        );
+
+        let invalid_js = parse_js(
+            r#"{ users: new Map([[1, "a"], [2, 3]]), flags: new Map([["x"]]), tags: new Set(), empty: new Set() }"#,
+            EsVersion::default(),
+        )?;
+
+        assert_eq!(super::validate::<AppState>(&invalid_js).len(), 2);
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Swatch {
+        primary: Color,
+        secondary: Color,
+        accent: Color,
+    }
+
+    #[test]
+    fn member_enum_variants() -> Result<(), Error> {
+        let source =
+            r#"{ primary: Color.Red, secondary: Api.Color.Green, accent: Api.Color["Red"] }"#;
+        let value = super::from_str::<Swatch>(source)?;
+
+        assert_eq!(
+            value,
+            Swatch {
+                primary: Color::Red,
+                secondary: Color::Green,
+                accent: Color::Red,
+            }
+        );
+
+        let options = super::Options::new().enum_prefix("Api");
+
+        assert!(matches!(
+            options.from_str::<Swatch>(source),
+            Err(super::Error::UnexpectedEnumObject(object)) if object == "Color"
+        ));
+        assert_eq!(
+            options.from_str::<Vec<Color>>("[Api.Green, Api.Color.Red]")?,
+            vec![Color::Green, Color::Red]
+        );
+        assert!(options.from_str::<Color>("Apis.Color.Red").is_err());
+        assert!(super::from_str::<Color>("Color.Blue").is_err());
+
+        Ok(())
+    }
+
+    #[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+    enum Shape<'a> {
+        Empty,
+        Circle(u32),
+        Point(i32, i32),
+        Label(&'a str, &'a str),
+        Rect { width: u32, height: u32 },
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Maybe {
+        Some(Point),
+        None,
+    }
+
+    #[test]
+    fn call_enum_variants() -> Result<(), Error> {
+        let source = r#"[Empty(), new Empty, Circle(3), new Point(1, 2), Label("a", "b"), Shapes.Rect({ width: 4, height: 5 })]"#;
+        let expected = vec![
+            Shape::Empty,
+            Shape::Empty,
+            Shape::Circle(3),
+            Shape::Point(1, 2),
+            Shape::Label("a", "b"),
+            Shape::Rect {
+                width: 4,
+                height: 5,
+            },
+        ];
+
+        let script_js = parse_js(source, EsVersion::default())?;
+
+        assert_eq!(super::from_expr::<Vec<Shape<'_>>>(&script_js)?, expected);
+        assert_eq!(
+            super::from_str::<Vec<Shape<'_>>>(&source.replace("Label(\"a\", \"b\"), ", ""))?,
+            expected
+                .into_iter()
+                .filter(|shape| !matches!(shape, Shape::Label(..)))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            super::from_str::<Vec<Maybe>>("[Some({ x: 1, y: 2 }), None]")?,
+            vec![Maybe::Some(Point { x: 1, y: 2 }), Maybe::None]
+        );
+
+        assert!(super::from_str::<Shape<'_>>("Empty(1)").is_err());
+        assert!(super::from_str::<Shape<'_>>("Circle(1, 2)").is_err());
+        assert!(super::from_str::<Shape<'_>>("Circle(...[1])").is_err());
+        assert!(super::from_str::<Shape<'_>>("Point(1)").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn enum_representations() -> Result<(), Error> {
+        let options = super::Options::new()
+            .array_tagged_enums(true)
+            .enum_variant_index(true);
+        let source =
+            r#"[["Empty"], ["Circle", 3], [2, 1, 2], ["Rect", { width: 4, height: 5 }], 0]"#;
+        let expected = vec![
+            Shape::Empty,
+            Shape::Circle(3),
+            Shape::Point(1, 2),
+            Shape::Rect {
+                width: 4,
+                height: 5,
+            },
+            Shape::Empty,
+        ];
+
+        let script_js = parse_js(source, EsVersion::default())?;
+
+        assert_eq!(options.from_expr::<Vec<Shape<'_>>>(&script_js)?, expected);
+        assert_eq!(options.from_str::<Vec<Shape<'_>>>(source)?, expected);
+        assert_eq!(
+            super::from_str::<Vec<Shape<'_>>>(
+                "[{ Empty: null }, { Empty: true }, { Empty: {} }, { Empty: undefined }]"
+            )?,
+            vec![Shape::Empty; 4]
+        );
+
+        assert!(super::from_str::<Shape<'_>>(r#"["Circle", 3]"#).is_err());
+        assert!(super::from_str::<Shape<'_>>("0").is_err());
+        assert!(super::from_str::<Shape<'_>>("{ Empty: false }").is_err());
+        assert!(options.from_str::<Shape<'_>>("[]").is_err());
+        assert!(options.from_str::<Shape<'_>>("[9]").is_err());
+        assert!(options.from_str::<Shape<'_>>("1.5").is_err());
+        assert!(options.from_str::<Shape<'_>>(r#"["Empty", 1]"#).is_err());
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(tag = "type")]
+    enum Internal {
+        Circle { radius: u32 },
+        Square { side: u32 },
+        Empty,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Circle(u32),
+        Point(i32, i32),
+        Empty,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        Bool(bool),
+        Integer(i64),
+        Float(f64),
+        Text(String),
+        Point(Point),
+        List(Vec<Self>),
+        Missing(Option<u8>),
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(untagged)]
+    enum AnyShape<'a> {
+        #[serde(borrow)]
+        Shape(Shape<'a>),
+        Name(String),
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Flattened<'a> {
+        id: u32,
+        #[serde(flatten)]
+        shape: Internal,
+        #[serde(flatten, borrow)]
+        rest: std::collections::BTreeMap<&'a str, serde_json::Value>,
+    }
+
+    #[test]
+    fn enum_representations_through_any() -> Result<(), Error> {
+        assert_eq!(
+            super::from_str::<Vec<Internal>>(
+                r#"[{ type: "Circle", radius: 1 }, { type: Square, side: (2) }, { type: 2 }]"#
+            )?,
+            vec![
+                Internal::Circle { radius: 1 },
+                Internal::Square { side: 2 },
+                Internal::Empty
+            ]
+        );
+        assert_eq!(
+            super::Options::new()
+                .enum_variant_index(true)
+                .from_str::<Vec<Adjacent>>(
+                    r#"[{ t: "Circle", c: 1 }, { t: Point, c: [-1, +2] }, { t: 2 }]"#
+                )?,
+            vec![Adjacent::Circle(1), Adjacent::Point(-1, 2), Adjacent::Empty]
+        );
+        assert_eq!(
+            super::from_str::<Vec<Untagged>>(
+                r#"[true, -3, 1.5, 7n, "text", Text, { x: 1, y: -2 }, [1, [-(2)]], undefined]"#
+            )?,
+            vec![
+                Untagged::Bool(true),
+                Untagged::Integer(-3),
+                Untagged::Float(1.5),
+                Untagged::Integer(7),
+                Untagged::Text("text".to_string()),
+                Untagged::Text("Text".to_string()),
+                Untagged::Point(Point { x: 1, y: -2 }),
+                Untagged::List(vec![
+                    Untagged::Integer(1),
+                    Untagged::List(vec![Untagged::Integer(-2)])
+                ]),
+                Untagged::Missing(None),
+            ]
+        );
+        // Members and calls are only read as variants by self-describing types when enabled.
+        let variants = super::Options::new().expressions_as_variants(true);
+
+        assert_eq!(
+            variants.from_str::<Vec<AnyShape<'_>>>(
+                r#"[Circle(3), new Point(1, 2), Empty(), Shapes.Rect({ width: 4, height: 5 }), "name"]"#
+            )?,
+            vec![
+                AnyShape::Shape(Shape::Circle(3)),
+                AnyShape::Shape(Shape::Point(1, 2)),
+                AnyShape::Shape(Shape::Empty),
+                AnyShape::Shape(Shape::Rect {
+                    width: 4,
+                    height: 5
+                }),
+                AnyShape::Name("name".to_string()),
+            ]
+        );
+        assert_eq!(
+            variants.from_str::<serde_json::Value>(
+                "[Circle(3), Point(1, 2), Empty(), Color.Red, -1, (2), undefined, NaN]"
+            )?,
+            serde_json::json!([
+                { "Circle": 3 },
+                { "Point": [1, 2] },
+                { "Empty": null },
+                "Red",
+                -1,
+                2,
+                null,
+                null
+            ])
+        );
+
+        let script_js = parse_js(
+            r#"{ id: 1, type: Circle, radius: 2, label: "big", color: 'red' }"#,
+            EsVersion::default(),
+        )?;
+
+        assert_eq!(
+            super::from_expr::<Flattened<'_>>(&script_js)?,
+            Flattened {
+                id: 1,
+                shape: Internal::Circle { radius: 2 },
+                // Flattened internally tagged enums do not consume their fields.
+                rest: [
+                    ("type", serde_json::json!("Circle")),
+                    ("radius", serde_json::json!(2)),
+                    ("label", serde_json::json!("big")),
+                    ("color", serde_json::json!("red")),
+                ]
+                .into_iter()
+                .collect(),
+            }
+        );
+
+        assert_eq!(super::from_str::<i32>("-5")?, -5);
+        assert_eq!(super::from_str::<Option<f64>>("-(1.5)")?, Some(-1.5));
+        assert_eq!(super::from_str::<Option<u8>>("undefined")?, None);
+        assert!(super::from_str::<u8>("-1").is_err());
+        assert!(super::from_str::<Vec<Internal>>("[{ type: 3 }]").is_err());
+        assert!(super::from_str::<Vec<Adjacent>>("[{ t: 2 }]").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn expressions_as_variants_in_any() -> Result<(), Error> {
+        for source in [
+            "{ mode: process.env.NODE_ENV, loc: window.location }",
+            "{ cb: foo(1), x: Math.max(1, 2, 3) }",
+            "[new Point(1, 2)]",
+        ] {
+            assert!(matches!(
+                super::from_str::<serde_json::Value>(source),
+                Err(super::Error::UnexpectedExpr(_))
+            ));
+            assert!(matches!(
+                super::from_str::<super::Value>(source),
+                Err(super::Error::UnexpectedExpr(_))
+            ));
+        }
+
+        assert_eq!(super::from_str::<Shape<'_>>("Shapes.Empty")?, Shape::Empty);
+
+        let variants = super::Options::new().expressions_as_variants(true);
+        let (value, warnings) =
+            variants.from_str_with_warnings::<serde_json::Value>("[window.foo, Math.max(1, 2)]")?;
+
+        assert_eq!(value, serde_json::json!(["foo", { "max": [1, 2] }]));
+        assert_eq!(
+            warnings
+                .into_iter()
+                .map(|warning| (warning.path.to_string(), warning.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "[0]".to_string(),
+                    WarningKind::ExpressionAsVariant("foo".to_string())
+                ),
+                (
+                    "[1]".to_string(),
+                    WarningKind::ExpressionAsVariant("max".to_string())
+                ),
+            ]
+        );
+
+        Ok(())
+    }
 }
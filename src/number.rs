@@ -1,12 +1,161 @@
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
 use serde::de::Unexpected;
-use swc_ecma_ast::Number;
+use swc_ecma_ast::{Expr, Lit, Number, UnaryExpr, UnaryOp};
+
+/// The value of a number literal, which may be negated or parenthesized.
+pub fn expr_to_f64(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Lit(Lit::Num(number)) => Some(number.value),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) => expr_to_f64(arg).map(|value| -value),
+        Expr::Paren(paren) => expr_to_f64(&paren.expr),
+        _ => None,
+    }
+}
+
+/// A negated, unary plus, or parenthesized number literal as a plain literal spanning the whole
+/// expression, so that `-1` can be read as a number. Returns `None` for plain literals.
+pub fn signed_literal(expr: &Expr) -> Option<Number> {
+    fn fold(expr: &Expr) -> Option<Number> {
+        match expr {
+            Expr::Lit(Lit::Num(number)) => Some(number.clone()),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Minus,
+                arg,
+                span,
+            }) => fold(arg).map(|number| Number {
+                span: *span,
+                value: -number.value,
+                raw: number.raw.map(|raw| format!("-{raw}").into()),
+            }),
+            Expr::Unary(UnaryExpr {
+                op: UnaryOp::Plus,
+                arg,
+                span,
+            }) => fold(arg).map(|number| Number {
+                span: *span,
+                ..number
+            }),
+            Expr::Paren(paren) => fold(&paren.expr).map(|number| Number {
+                span: paren.span,
+                ..number
+            }),
+            _ => None,
+        }
+    }
+
+    match expr {
+        Expr::Lit(_) => None,
+        other => fold(other),
+    }
+}
 
 pub fn is_integer(number: &Number) -> bool {
     number
         .raw
         .as_ref()
-        .filter(|atom| !atom.as_str().contains('.'))
-        .is_some()
+        .is_some_and(|atom| !atom.as_str().contains('.'))
+        && number.value.fract() == 0.0
+}
+
+/// Format a number as JavaScript's `Number.prototype.toString` does.
+pub fn to_js_string(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    } else if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    } else if value == 0.0 {
+        return "0".to_string();
+    }
+
+    // Rust gives the shortest digits that round-trip, which is what JavaScript uses.
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let digits = mantissa.replace('.', "");
+    let length = i64::try_from(digits.len()).unwrap_or(i64::MAX);
+    // The position of the decimal point relative to the start of the digits.
+    let point = exponent.parse::<i64>().unwrap_or_default() + 1;
+
+    let magnitude = if length <= point && point <= 21 {
+        digits + "0".repeat((point - length) as usize).as_str()
+    } else if 0 < point && point <= 21 {
+        format!(
+            "{}.{}",
+            &digits[..point as usize],
+            &digits[point as usize..]
+        )
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{digits}", "0".repeat(point.unsigned_abs() as usize))
+    } else {
+        let sign = if point > 0 { '+' } else { '-' };
+        let exponent = (point - 1).abs();
+
+        if length == 1 {
+            format!("{digits}e{sign}{exponent}")
+        } else {
+            format!("{}.{}e{sign}{exponent}", &digits[..1], &digits[1..])
+        }
+    };
+
+    if value < 0.0 {
+        format!("-{magnitude}")
+    } else {
+        magnitude
+    }
+}
+
+/// Whether JavaScript treats the character as white space or a line terminator.
+pub const fn is_js_whitespace(ch: char) -> bool {
+    (ch.is_whitespace() && ch != '\u{85}') || ch == '\u{feff}'
+}
+
+/// Convert a string to a number as JavaScript's `Number` function does.
+pub fn string_to_number(string: &str) -> f64 {
+    let string = string.trim_matches(is_js_whitespace);
+
+    let radix = match string.get(..2) {
+        Some("0x" | "0X") => 16,
+        Some("0o" | "0O") => 8,
+        Some("0b" | "0B") => 2,
+        _ => 10,
+    };
+
+    if radix != 10 {
+        let digits = &string[2..];
+
+        return if !digits.is_empty() && digits.chars().all(|ch| ch.is_digit(radix)) {
+            digits.chars().fold(0.0, |value, ch| {
+                value.mul_add(
+                    f64::from(radix),
+                    f64::from(ch.to_digit(radix).unwrap_or_default()),
+                )
+            })
+        } else {
+            f64::NAN
+        };
+    }
+
+    match string.strip_prefix(['+', '-']).unwrap_or(string) {
+        "" if string.is_empty() => 0.0,
+        "Infinity" if string.starts_with('-') => f64::NEG_INFINITY,
+        "Infinity" => f64::INFINITY,
+        // Rust also accepts names such as `inf` and `nan`, which JavaScript does not.
+        unsigned
+            if unsigned.bytes().all(|byte| {
+                byte.is_ascii_digit() || matches!(byte, b'.' | b'e' | b'E' | b'+' | b'-')
+            }) =>
+        {
+            string.parse().unwrap_or(f64::NAN)
+        }
+        _ => f64::NAN,
+    }
 }
 
 pub fn number_to_unexpected(number: &Number) -> Option<Unexpected<'_>> {
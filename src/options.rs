@@ -0,0 +1,489 @@
+use crate::context::Context;
+use crate::error::Error;
+use crate::path::Path;
+#[cfg(feature = "parser")]
+use crate::script::Script;
+use crate::value::Value;
+use crate::warning::Warning;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use swc_common::Spanned as _;
+use swc_ecma_ast::{EsVersion, Expr};
+
+/// Configuration for deserialization.
+#[derive(Clone, Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Options {
+    pub(crate) limits: Limits,
+    pub(crate) regex_as_string: bool,
+    pub(crate) enum_prefix: Option<String>,
+    pub(crate) array_tagged_enums: bool,
+    pub(crate) enum_variant_index: bool,
+    pub(crate) expressions_as_variants: bool,
+    pub(crate) fold_constants: bool,
+    pub(crate) decode_strings: bool,
+    pub(crate) globals: HashMap<String, Value>,
+    /// Whether the environment variables of `process.env` were given.
+    pub(crate) env: bool,
+    version: EsVersion,
+}
+
+/// Bounds on the resources used while deserializing untrusted input.
+///
+/// By default only the nesting depth and the number of steps taken to fold constants are limited,
+/// since the parser and the deserializer are recursive and can otherwise overflow the stack, and a
+/// few bindings that each refer to the previous one twice can otherwise take exponential time to
+/// fold. Both limits can be raised.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Limits {
+    /// The maximum nesting depth of arrays and objects, and of brackets in source text that is
+    /// parsed. This is [`Limits::DEFAULT_MAX_DEPTH`] by default.
+    pub max_depth: Option<usize>,
+    /// The maximum number of elements in a single array or properties in a single object.
+    pub max_length: Option<usize>,
+    /// The maximum total number of bytes in all strings and keys that are deserialized.
+    pub max_string_bytes: Option<usize>,
+    /// The maximum size in bytes of source text to parse.
+    pub max_input_bytes: Option<usize>,
+    /// The maximum number of operators evaluated when folding constants, over all of the input.
+    /// This is [`Limits::DEFAULT_MAX_FOLD_STEPS`] by default.
+    pub max_fold_steps: Option<usize>,
+}
+
+impl Limits {
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+    pub const DEFAULT_MAX_FOLD_STEPS: usize = 100_000;
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(Self::DEFAULT_MAX_DEPTH),
+            max_length: None,
+            max_string_bytes: None,
+            max_input_bytes: None,
+            max_fold_steps: Some(Self::DEFAULT_MAX_FOLD_STEPS),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Limit {
+    Depth(usize),
+    Length(usize),
+    StringBytes(usize),
+    InputBytes(usize),
+    FoldSteps(usize),
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Depth(limit) => write!(f, "nesting depth of {limit}"),
+            Self::Length(limit) => write!(f, "length of {limit}"),
+            Self::StringBytes(limit) => write!(f, "{limit} total string bytes"),
+            Self::InputBytes(limit) => write!(f, "input size of {limit} bytes"),
+            Self::FoldSteps(limit) => write!(f, "{limit} folding steps"),
+        }
+    }
+}
+
+impl Options {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    #[must_use]
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.limits.max_depth = Some(max_depth);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_length(mut self, max_length: usize) -> Self {
+        self.limits.max_length = Some(max_length);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_string_bytes(mut self, max_string_bytes: usize) -> Self {
+        self.limits.max_string_bytes = Some(max_string_bytes);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_input_bytes(mut self, max_input_bytes: usize) -> Self {
+        self.limits.max_input_bytes = Some(max_input_bytes);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_fold_steps(mut self, max_fold_steps: usize) -> Self {
+        self.limits.max_fold_steps = Some(max_fold_steps);
+        self
+    }
+
+    /// Deserialize regular expressions as strings in literal syntax, such as `/a+b/gi`.
+    #[must_use]
+    pub const fn regex_as_string(mut self, regex_as_string: bool) -> Self {
+        self.regex_as_string = regex_as_string;
+        self
+    }
+
+    /// Require the object of member expressions used as enum variants (such as `Color` in
+    /// `Color.Red`) to be the given name or to be nested within it.
+    ///
+    /// For example, the prefix `Api` accepts `Api.Color.Red` and `Api.Red`, but not `Color.Red`.
+    #[must_use]
+    pub fn enum_prefix<S: Into<String>>(mut self, enum_prefix: S) -> Self {
+        self.enum_prefix = Some(enum_prefix.into());
+        self
+    }
+
+    /// Accept enums written as arrays whose first element is the variant, such as `["Move", 1, 2]`.
+    ///
+    /// The remaining elements are the fields of a tuple variant, or the single value of a newtype
+    /// or struct variant.
+    #[must_use]
+    pub const fn array_tagged_enums(mut self, array_tagged_enums: bool) -> Self {
+        self.array_tagged_enums = array_tagged_enums;
+        self
+    }
+
+    /// Accept numbers as enum variants, interpreted as positions in the list of variants.
+    #[must_use]
+    pub const fn enum_variant_index(mut self, enum_variant_index: bool) -> Self {
+        self.enum_variant_index = enum_variant_index;
+        self
+    }
+
+    /// Deserialize member and call expressions that cannot be evaluated as the enum variants they
+    /// name, even when the target type is self-describing, such as `serde_json::Value` or an
+    /// untagged enum.
+    ///
+    /// By default these are only accepted where an enum is expected, and are otherwise an error,
+    /// since reading `window.location` as the string `"location"` would lose data. When this is
+    /// enabled, a warning is reported for each such expression.
+    #[must_use]
+    pub const fn expressions_as_variants(mut self, expressions_as_variants: bool) -> Self {
+        self.expressions_as_variants = expressions_as_variants;
+        self
+    }
+
+    /// Evaluate operators whose operands are literals before deserializing, so that expressions
+    /// such as `60 * 60 * 24`, `"a" + "b"`, and `!0` are read as their values.
+    ///
+    /// Unary, binary, conditional, and comma operators are evaluated as JavaScript would evaluate
+    /// them, after resolving the identifiers in their operands. Expressions that have other
+    /// operands, or that would throw, are left unchanged. Operators are folded as they are
+    /// deserialized, so only folded values cannot be borrowed from the input.
+    #[must_use]
+    pub const fn fold_constants(mut self, fold_constants: bool) -> Self {
+        self.fold_constants = fold_constants;
+        self
+    }
+
+    /// Evaluate calls of the global functions that decode strings, so that expressions such as
+    /// `atob("aGk=")`, `decodeURIComponent("%C3%A9")`, `unescape("%u00e9")`, and
+    /// `String.fromCharCode(104, 105)` are read as the strings they return.
+    ///
+    /// The arguments must be strings (or numbers for `String.fromCharCode`), possibly themselves
+    /// given by such calls. When deserializing bytes, the result of `atob` is its decoded bytes,
+    /// and the result of the other functions is encoded as UTF-8.
+    #[must_use]
+    pub const fn decode_strings(mut self, decode_strings: bool) -> Self {
+        self.decode_strings = decode_strings;
+        self
+    }
+
+    /// Define a global variable, which identifiers that are not bound in the script resolve to.
+    ///
+    /// A name such as `process.env.NODE_ENV` defines a property of an object that is not bound,
+    /// which member expressions with that path resolve to. Defaults such as
+    /// `process.env.API_URL ?? "default"` and conditionals on these values are evaluated when
+    /// [`Self::fold_constants`] is enabled.
+    #[must_use]
+    pub fn global<S: Into<String>, V: Into<Value>>(mut self, name: S, value: V) -> Self {
+        self.globals.insert(name.into(), value.into());
+        self
+    }
+
+    /// Define a global variable whose value is any serializable Rust value.
+    pub fn global_serialized<S: Into<String>, T: serde::Serialize + ?Sized>(
+        self,
+        name: S,
+        value: &T,
+    ) -> Result<Self, Error> {
+        Ok(self.global(name, crate::value::to_value(value)?))
+    }
+
+    /// Define the environment variables that `process.env` contains, such as the values from
+    /// `std::env::vars()`. Once this is called, any variable of `process.env` that is not defined
+    /// is `undefined`. Otherwise only the variables given with [`Self::global`] are known.
+    #[must_use]
+    pub fn env<I: IntoIterator<Item = (K, V)>, K: AsRef<str>, V: Into<String>>(
+        mut self,
+        vars: I,
+    ) -> Self {
+        for (name, value) in vars {
+            self.globals.insert(
+                format!("process.env.{}", name.as_ref()),
+                Value::String(value.into()),
+            );
+        }
+
+        self.env = true;
+        self
+    }
+
+    /// The ECMAScript version used when parsing source text.
+    #[must_use]
+    pub const fn version(mut self, version: EsVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn from_expr<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr: &'a Expr,
+    ) -> Result<T, Error> {
+        Self::deserialize(Cow::Borrowed(expr), Context::with_options(self.clone()))
+    }
+
+    /// Deserialize an expression that was parsed from the given source text.
+    ///
+    /// Its spans must be byte offsets into the source, as they are when parsing with a start
+    /// position of zero. This makes line and column information available to [`crate::Spanned`].
+    pub fn from_expr_with_source<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr: &'a Expr,
+        source: &'a str,
+    ) -> Result<T, Error> {
+        Self::deserialize(
+            Cow::Borrowed(expr),
+            Context::with_options(self.clone()).with_source(source),
+        )
+    }
+
+    #[cfg(feature = "parser")]
+    pub fn from_str<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr_str: &'a str,
+    ) -> Result<T, Error> {
+        let expr = self.parse_expr(expr_str)?;
+
+        Self::deserialize(
+            Cow::Owned(*expr),
+            Context::with_options(self.clone()).with_source(expr_str),
+        )
+    }
+
+    /// Deserialize the expression, also returning warnings for any lossy conversions, as
+    /// [`crate::from_expr_with_warnings`] does.
+    pub fn from_expr_with_warnings<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr: &'a Expr,
+    ) -> Result<(T, Vec<Warning>), Error> {
+        let context = Context::collecting_warnings(self.clone());
+        let value = Self::deserialize(Cow::Borrowed(expr), context.clone())?;
+
+        Ok((value, context.take_warnings()))
+    }
+
+    /// Deserialize the string, also returning warnings for any lossy conversions.
+    #[cfg(feature = "parser")]
+    pub fn from_str_with_warnings<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr_str: &'a str,
+    ) -> Result<(T, Vec<Warning>), Error> {
+        let expr = self.parse_expr(expr_str)?;
+        let context = Context::collecting_warnings(self.clone()).with_source(expr_str);
+        let value = Self::deserialize(Cow::Owned(*expr), context.clone())?;
+
+        Ok((value, context.take_warnings()))
+    }
+
+    /// Deserialize the expression, also returning the paths of all values ignored by the target
+    /// type, as [`crate::from_expr_with_ignored`] does.
+    pub fn from_expr_with_ignored<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr: &'a Expr,
+    ) -> Result<(T, Vec<Path>), Error> {
+        let context = Context::collecting_ignored(self.clone());
+        let value = Self::deserialize(Cow::Borrowed(expr), context.clone())?;
+
+        Ok((value, context.take_ignored()))
+    }
+
+    /// Deserialize the string, also returning the paths of all values ignored by the target type.
+    #[cfg(feature = "parser")]
+    pub fn from_str_with_ignored<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr_str: &'a str,
+    ) -> Result<(T, Vec<Path>), Error> {
+        let expr = self.parse_expr(expr_str)?;
+        let context = Context::collecting_ignored(self.clone()).with_source(expr_str);
+        let value = Self::deserialize(Cow::Owned(*expr), context.clone())?;
+
+        Ok((value, context.take_ignored()))
+    }
+
+    /// Deserialize the expression, continuing past recoverable mismatches, and return every
+    /// problem, as [`crate::validate`] does.
+    #[must_use]
+    pub fn validate<'a: 'de, 'de, T: serde::Deserialize<'de>>(&self, expr: &'a Expr) -> Vec<Error> {
+        Self::validate_in::<T>(Cow::Borrowed(expr), &Context::accumulating(self.clone()))
+    }
+
+    /// Parse and validate the string. An error in parsing it is the only problem returned.
+    #[cfg(feature = "parser")]
+    #[must_use]
+    pub fn validate_str<'a: 'de, 'de, T: serde::Deserialize<'de>>(
+        &self,
+        expr_str: &'a str,
+    ) -> Vec<Error> {
+        match self.parse_expr(expr_str) {
+            Ok(expr) => Self::validate_in::<T>(
+                Cow::Owned(*expr),
+                &Context::accumulating(self.clone()).with_source(expr_str),
+            ),
+            Err(error) => vec![error],
+        }
+    }
+
+    /// Parse a script or module, whose top-level bindings can then be deserialized.
+    #[cfg(feature = "parser")]
+    pub fn parse_script<'a>(&self, source: &'a str) -> Result<Script<'a>, Error> {
+        self.check_input(source)?;
+
+        Ok(Script::with_source(
+            crate::parse_program(source, self.version)?,
+            source,
+            self.clone(),
+        ))
+    }
+
+    /// Deserialize the exports of a `CommonJS` module, as [`Script::exports`] does.
+    #[cfg(feature = "parser")]
+    pub fn from_commonjs_str<T: serde::de::DeserializeOwned>(
+        &self,
+        source: &str,
+    ) -> Result<T, Error> {
+        self.parse_script(source)?.exports()
+    }
+
+    #[cfg(feature = "parser")]
+    pub(crate) fn parse_expr(&self, expr_str: &str) -> Result<Box<Expr>, Error> {
+        self.check_input(expr_str)?;
+
+        crate::parse_expr(expr_str, self.version)
+    }
+
+    #[cfg(any(feature = "json", feature = "parser"))]
+    pub(crate) fn check_input(&self, source: &str) -> Result<(), Error> {
+        if let Some(limit) = self.limits.max_input_bytes
+            && source.len() > limit
+        {
+            return Err(Error::LimitExceeded(Limit::InputBytes(limit)));
+        }
+
+        // The parser is recursive, so we check nesting before handing it the input.
+        if let Some(limit) = self.limits.max_depth
+            && source_nesting_exceeds(source, limit)
+        {
+            return Err(Error::LimitExceeded(Limit::Depth(limit)));
+        }
+
+        Ok(())
+    }
+
+    fn deserialize<'de, T: serde::Deserialize<'de>>(
+        expr: Cow<'de, Expr>,
+        context: Context<'de>,
+    ) -> Result<T, Error> {
+        T::deserialize(crate::Deserializer::resolved(expr, context)?)
+    }
+
+    fn validate_in<'de, T: serde::Deserialize<'de>>(
+        expr: Cow<'de, Expr>,
+        context: &Context<'de>,
+    ) -> Vec<Error> {
+        let span = expr.span();
+        let result = crate::Deserializer::resolved(expr, context.clone()).and_then(T::deserialize);
+
+        let mut errors = context.take_errors();
+
+        if let Err(error) = result {
+            errors.push(context.locate(error, span));
+        }
+
+        errors
+    }
+}
+
+/// Conservatively estimate whether brackets in the source are nested more deeply than the limit.
+///
+/// String literals and comments are skipped, but other contexts (such as template and regular
+/// expression literals) are not recognized, so this may overestimate the depth of unusual input.
+#[cfg(any(feature = "json", feature = "parser"))]
+fn source_nesting_exceeds(source: &str, limit: usize) -> bool {
+    let mut depth = 0_usize;
+    let mut chars = source.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '[' | '{' | '(' => {
+                depth += 1;
+
+                if depth > limit {
+                    return true;
+                }
+            }
+            ']' | '}' | ')' => {
+                depth = depth.saturating_sub(1);
+            }
+            '\\' => {
+                chars.next();
+            }
+            '"' | '\'' | '`' => {
+                while let Some(next) = chars.next() {
+                    if next == '\\' {
+                        chars.next();
+                    } else if next == ch {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+
+                let mut previous = None;
+
+                for next in chars.by_ref() {
+                    if previous == Some('*') && next == '/' {
+                        break;
+                    }
+
+                    previous = Some(next);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    false
+}
@@ -0,0 +1,109 @@
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+/// A location inside a JavaScript value, written the way it would be accessed in JavaScript.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Path {
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl Path {
+    #[must_use]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    #[must_use]
+    pub const fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.segments.is_empty() {
+            return write!(f, ".");
+        }
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Key(key) if is_identifier(key) => {
+                    if i > 0 {
+                        write!(f, ".")?;
+                    }
+
+                    write!(f, "{key}")?;
+                }
+                Segment::Key(key) => write!(f, "[{key:?}]")?,
+                Segment::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_alphabetic() || ch == '_' || ch == '$')
+        && chars.all(|ch| ch.is_alphanumeric() || ch == '_' || ch == '$')
+}
+
+/// Persistent linked representation used while deserializing, so that descending into a child
+/// does not copy the path of its parent.
+#[derive(Debug)]
+pub(crate) struct Node {
+    parent: Option<Rc<Self>>,
+    segment: Segment,
+}
+
+impl Node {
+    pub(crate) fn child(parent: Option<&Rc<Self>>, segment: Segment) -> Rc<Self> {
+        Rc::new(Self {
+            parent: parent.cloned(),
+            segment,
+        })
+    }
+
+    pub(crate) fn to_path(node: Option<&Rc<Self>>) -> Path {
+        let mut segments = vec![];
+        let mut current = node;
+
+        while let Some(node) = current {
+            segments.push(node.segment.clone());
+            current = node.parent.as_ref();
+        }
+
+        segments.reverse();
+
+        Path { segments }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Node, Segment};
+
+    #[test]
+    fn display() {
+        let root = Node::child(None, Segment::Key("data".to_string()));
+        let index = Node::child(Some(&root), Segment::Index(0));
+        let key = Node::child(Some(&index), Segment::Key("some key".to_string()));
+        let name = Node::child(Some(&key), Segment::Key("name".to_string()));
+
+        assert_eq!(Node::to_path(None).to_string(), ".");
+        assert_eq!(
+            Node::to_path(Some(&name)).to_string(),
+            r#"data[0]["some key"].name"#
+        );
+    }
+}
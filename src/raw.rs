@@ -0,0 +1,197 @@
+use crate::context::Context;
+use crate::error::Error;
+use crate::options::Options;
+use serde::de::{Deserialize, Deserializer, Visitor};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use swc_common::Spanned as _;
+use swc_ecma_ast::Expr;
+
+pub(crate) const JS_NAME: &str = "$__ecmade_private_RawJs";
+
+/// The original source text of a JavaScript expression, captured without deserializing it.
+///
+/// This accepts any expression, including functions, regular expressions, and calls. The source
+/// text must be available, which is the case when using [`crate::from_str`] or
+/// [`crate::Options::from_expr_with_source`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RawJs<'a> {
+    source: Cow<'a, str>,
+}
+
+impl<'a> RawJs<'a> {
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    #[must_use]
+    pub fn into_owned(self) -> RawJs<'static> {
+        RawJs {
+            source: Cow::Owned(self.source.into_owned()),
+        }
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> Cow<'a, str> {
+        self.source
+    }
+}
+
+impl std::fmt::Display for RawJs<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl serde::Serialize for RawJs<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.source)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawJs<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(JS_NAME, RawJsVisitor)
+    }
+}
+
+struct RawJsVisitor;
+
+impl<'de> Visitor<'de> for RawJsVisitor {
+    type Value = RawJs<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("JavaScript source text")
+    }
+
+    fn visit_borrowed_str<E: serde::de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(RawJs {
+            source: Cow::Borrowed(v),
+        })
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(RawJs {
+            source: Cow::Owned(v.to_string()),
+        })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+pub(crate) const EXPR_NAME: &str = "$__ecmade_private_RawExpr";
+
+thread_local! {
+    /// The expression and context of the [`RawExpr`] being deserialized, which serde's visitors
+    /// can only be given as data that does not borrow anything.
+    static CAPTURED: RefCell<Option<(Expr, Context<'static>)>> = const { RefCell::new(None) };
+}
+
+/// A JavaScript expression whose deserialization is deferred, in the way that `serde_json`'s
+/// `RawValue` defers JSON.
+///
+/// This is useful when the target type depends on another part of the input, such as a sibling
+/// discriminator field. The expression keeps the context it was found in, so identifiers bound in
+/// the script, globals, and the options are the same when it is deserialized with
+/// [`RawExpr::into_value`]. The scope of the script is copied, so this is not free for large
+/// scripts.
+///
+/// It can only be deserialized by this crate.
+#[derive(Clone, Debug)]
+pub struct RawExpr<'de> {
+    expr: Cow<'de, Expr>,
+    context: Context<'de>,
+}
+
+impl<'de> RawExpr<'de> {
+    #[must_use]
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> Cow<'de, Expr> {
+        self.expr
+    }
+
+    /// The source text of the expression, if the source is available.
+    #[must_use]
+    pub fn source_text(&self) -> Option<&'de str> {
+        let span = self.expr.span();
+
+        if span.is_dummy() {
+            None
+        } else {
+            self.context.source_text(span)
+        }
+    }
+
+    /// Deserialize the expression with the options it was found with.
+    ///
+    /// Recoverable errors are returned rather than collected, and no warnings are reported.
+    pub fn into_value<T: Deserialize<'de>>(self) -> Result<T, Error> {
+        let options = self.context.options().clone();
+
+        self.into_value_with(&options)
+    }
+
+    /// Deserialize the expression with other options, in the scope it was found in.
+    pub fn into_value_with<T: Deserialize<'de>>(self, options: &Options) -> Result<T, Error> {
+        let context = self.context.deferred(options.clone());
+
+        T::deserialize(crate::Deserializer::resolved(self.expr, context)?)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawExpr<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(EXPR_NAME, RawExprVisitor)
+    }
+}
+
+/// Keep an expression and its context for the visitor of a [`RawExpr`], which the deserializer
+/// then gives the source text, if it is available, or a unit.
+pub(crate) fn capture(expr: Cow<'_, Expr>, context: Context<'_>) {
+    CAPTURED.set(Some((expr.into_owned(), context.into_owned())));
+}
+
+struct RawExprVisitor;
+
+impl RawExprVisitor {
+    fn captured<'de, E: serde::de::Error>(source: Option<&'de str>) -> Result<RawExpr<'de>, E> {
+        let (expr, context) = CAPTURED
+            .take()
+            .ok_or_else(|| E::custom("a RawExpr can only be deserialized by ecmade"))?;
+        let context: Context<'de> = context;
+
+        Ok(RawExpr {
+            expr: Cow::Owned(expr),
+            context: match source {
+                Some(source) => context.with_source(source),
+                None => context,
+            },
+        })
+    }
+}
+
+impl<'de> Visitor<'de> for RawExprVisitor {
+    type Value = RawExpr<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a JavaScript expression")
+    }
+
+    fn visit_borrowed_str<E: serde::de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Self::captured(Some(v))
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Self::captured(None)
+    }
+}
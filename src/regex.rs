@@ -0,0 +1,217 @@
+use crate::error::Error;
+use serde::de::{Deserialize, Deserializer, Visitor};
+use swc_ecma_ast::{Callee, Expr, ExprOrSpread, Lit};
+
+pub(crate) const NAME: &str = "$__ecmade_private_Regex";
+
+/// A JavaScript regular expression.
+///
+/// This can be deserialized from a regular expression literal such as `/a+b/gi`, a construction
+/// such as `new RegExp("a+b", "gi")`, or (in other formats) a string in literal syntax.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Regex {
+    pub pattern: String,
+    pub flags: String,
+}
+
+impl Regex {
+    #[must_use]
+    pub fn new<P: Into<String>, F: Into<String>>(pattern: P, flags: F) -> Self {
+        Self {
+            pattern: pattern.into(),
+            flags: flags.into(),
+        }
+    }
+
+    /// Parse a regular expression in literal syntax, such as `/a+b/gi`.
+    #[must_use]
+    pub fn from_literal(literal: &str) -> Option<Self> {
+        let (pattern, flags) = literal.strip_prefix('/')?.rsplit_once('/')?;
+
+        Some(Self::new(pattern, flags))
+    }
+
+    /// Convert to a Rust regular expression.
+    ///
+    /// The `i`, `m`, and `s` flags are supported, and `g` and `d` are ignored, since they do not
+    /// affect what is matched. The character classes `\d` and `\w` match only ASCII characters,
+    /// as in JavaScript. Syntax that the `regex` crate does not support (such as backreferences
+    /// and lookaround) results in an error.
+    #[cfg(feature = "regex")]
+    pub fn to_regex(&self) -> Result<::regex::Regex, Error> {
+        let mut builder = ::regex::RegexBuilder::new(&translate_pattern(&self.pattern));
+
+        for flag in self.flags.chars() {
+            match flag {
+                'i' => builder.case_insensitive(true),
+                'm' => builder.multi_line(true),
+                's' => builder.dot_matches_new_line(true),
+                'g' | 'd' | 'u' => &mut builder,
+                other => return Err(Error::UnsupportedRegexFlag(other)),
+            };
+        }
+
+        builder.build().map_err(Error::InvalidRegex)
+    }
+}
+
+impl std::fmt::Display for Regex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "/{}/{}", self.pattern, self.flags)
+    }
+}
+
+impl From<&swc_ecma_ast::Regex> for Regex {
+    fn from(regex: &swc_ecma_ast::Regex) -> Self {
+        Self::new(regex.exp.as_str(), regex.flags.as_str())
+    }
+}
+
+impl serde::Serialize for Regex {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Regex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(NAME, RegexVisitor)
+    }
+}
+
+struct RegexVisitor;
+
+impl<'de> Visitor<'de> for RegexVisitor {
+    type Value = Regex;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a regular expression")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Regex::from_literal(v).ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+/// Read a regular expression literal or a `RegExp` construction.
+///
+/// Returns `None` if the expression is neither.
+pub(crate) fn from_expr(expr: &Expr) -> Option<Result<Regex, Error>> {
+    let args = match expr {
+        Expr::Lit(Lit::Regex(regex)) => return Some(Ok(regex.into())),
+        Expr::New(new) if is_reg_exp(&new.callee) => new.args.as_deref().unwrap_or_default(),
+        Expr::Call(call) => match &call.callee {
+            Callee::Expr(callee) if is_reg_exp(callee) => &call.args,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let regex = match (Arg::new(args.first()), Arg::new(args.get(1))) {
+        (Arg::Missing, Arg::Missing) => Some(Regex::new(source_from_string(""), "")),
+        (Arg::Str(pattern), Arg::Missing) => Some(Regex::new(source_from_string(&pattern), "")),
+        (Arg::Str(pattern), Arg::Str(flags)) => {
+            Some(Regex::new(source_from_string(&pattern), flags))
+        }
+        // An existing regular expression can be copied, optionally with new flags.
+        (Arg::Regex(regex), Arg::Missing) => Some(regex),
+        (Arg::Regex(regex), Arg::Str(flags)) => Some(Regex::new(regex.pattern, flags)),
+        _ => None,
+    };
+
+    Some(
+        regex
+            .filter(|_| args.len() <= 2)
+            .ok_or_else(|| Error::UnexpectedExpr(expr.clone())),
+    )
+}
+
+/// An argument to the `RegExp` constructor.
+enum Arg {
+    Missing,
+    Str(String),
+    Regex(Regex),
+    Unsupported,
+}
+
+impl Arg {
+    fn new(arg: Option<&ExprOrSpread>) -> Self {
+        match arg {
+            None => Self::Missing,
+            Some(ExprOrSpread { spread: None, expr }) => match &**expr {
+                Expr::Lit(Lit::Str(str)) => Self::Str(str.value.to_string()),
+                Expr::Lit(Lit::Regex(regex)) => Self::Regex(regex.into()),
+                Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+                    .quasis
+                    .iter()
+                    .map(|quasi| quasi.cooked.as_deref())
+                    .collect::<Option<String>>()
+                    .map_or(Self::Unsupported, Self::Str),
+                _ => Self::Unsupported,
+            },
+            Some(_) => Self::Unsupported,
+        }
+    }
+}
+
+/// The literal source of a pattern given as a string, as JavaScript's `RegExp.prototype.source`.
+fn source_from_string(pattern: &str) -> String {
+    if pattern.is_empty() {
+        return "(?:)".to_string();
+    }
+
+    let mut result = String::with_capacity(pattern.len());
+    let mut escaped = false;
+
+    for ch in pattern.chars() {
+        if ch == '/' && !escaped {
+            result.push('\\');
+        }
+
+        escaped = ch == '\\' && !escaped;
+        result.push(ch);
+    }
+
+    result
+}
+
+fn is_reg_exp(callee: &Expr) -> bool {
+    callee
+        .as_ident()
+        .is_some_and(|ident| ident.sym.as_str() == "RegExp")
+}
+
+/// Replace character class escapes whose meaning differs between JavaScript and Rust.
+#[cfg(feature = "regex")]
+fn translate_pattern(pattern: &str) -> String {
+    let mut result = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('d') => result.push_str("[0-9]"),
+                Some('D') => result.push_str("[^0-9]"),
+                Some('w') => result.push_str("[0-9A-Za-z_]"),
+                Some('W') => result.push_str("[^0-9A-Za-z_]"),
+                Some('/') => result.push('/'),
+                Some(other) => {
+                    result.push('\\');
+                    result.push(other);
+                }
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+
+    result
+}
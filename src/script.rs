@@ -0,0 +1,1324 @@
+use crate::context::Context;
+use crate::error::Error;
+use crate::fold::Constant;
+use crate::options::Options;
+use crate::path::Path;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+use swc_common::DUMMY_SP;
+use swc_common::Spanned as _;
+use swc_ecma_ast::{
+    ArrayLit, AssignExpr, AssignOp, AssignTarget, BinaryOp, BlockStmtOrExpr, CallExpr, Callee,
+    ComputedPropName, Decl, Expr, ExprOrSpread, ExprStmt, FnExpr, Ident, IdentName, KeyValueProp,
+    Lit, MemberExpr, MemberProp, ModuleDecl, ModuleItem, Number, ObjectLit, Pat, Program, Prop,
+    PropName, PropOrSpread, ReturnStmt, SimpleAssignTarget, Stmt, UnaryExpr, UnaryOp, UpdateExpr,
+    VarDecl,
+};
+
+/// A script or module whose top-level bindings can be deserialized.
+///
+/// Bindings are made by `const`, `let`, and `var` declarations and by assignments in top-level
+/// expression statements, including assignments to properties such as `window.state = { ... }`.
+/// While deserializing a binding, identifiers that refer to other bindings (including shorthand
+/// properties such as `{ a, b }`) are replaced by their values. A binding that is assigned more
+/// than once, or whose value refers to itself, is an error when it is used. The value returned
+/// by an immediately invoked function, such as `(function (a) { return { a }; })(1)`, is
+/// evaluated with its parameters bound to the arguments.
+///
+/// ```
+/// use ecmade::{Script, Value};
+///
+/// let script = Script::parse(r#"var a = "x", b = [1, 2]; window.state = { a, b };"#)?;
+/// let state: Value = script.get("window.state")?;
+///
+/// assert_eq!(state["a"], Value::from("x"));
+/// assert_eq!(state["b"][1], Value::from(2));
+/// # Ok::<(), ecmade::error::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Script<'a> {
+    program: Program,
+    source: Option<&'a str>,
+    options: Options,
+}
+
+impl<'a> Script<'a> {
+    #[must_use]
+    pub fn new(program: Program) -> Self {
+        Self {
+            program,
+            source: None,
+            options: Options::default(),
+        }
+    }
+
+    /// Use the options, including their limits, when deserializing bindings of the program.
+    #[must_use]
+    pub fn with_options(self, options: Options) -> Self {
+        Self { options, ..self }
+    }
+
+    /// The spans of the program must be byte offsets into the source.
+    #[cfg(feature = "parser")]
+    pub(crate) const fn with_source(program: Program, source: &'a str, options: Options) -> Self {
+        Self {
+            program,
+            source: Some(source),
+            options,
+        }
+    }
+
+    #[cfg(feature = "parser")]
+    pub fn parse(source: &'a str) -> Result<Self, Error> {
+        Options::new().parse_script(source)
+    }
+
+    /// Deserialize the value bound to a name, which is either an identifier or a dotted path
+    /// that was assigned to, such as `window.state`. A dotted path may also refer to a property
+    /// of a bound value, such as `config.items`.
+    pub fn get<'de, T: serde::Deserialize<'de>>(&'de self, name: &str) -> Result<T, Error>
+    where
+        'a: 'de,
+    {
+        self.get_in(name, Context::with_options(self.options.clone()))
+    }
+
+    /// Deserialize the value bound to a name, as [`Self::get`] does, also returning the paths of
+    /// all values ignored by the target type.
+    pub fn get_with_ignored<'de, T: serde::Deserialize<'de>>(
+        &'de self,
+        name: &str,
+    ) -> Result<(T, Vec<Path>), Error>
+    where
+        'a: 'de,
+    {
+        let context = Context::collecting_ignored(self.options.clone());
+        let value = self.get_in(name, context.clone())?;
+
+        Ok((value, context.take_ignored()))
+    }
+
+    fn get_in<'de, T: serde::Deserialize<'de>>(
+        &'de self,
+        name: &str,
+        context: Context<'de>,
+    ) -> Result<T, Error>
+    where
+        'a: 'de,
+    {
+        let mut segments = name.split('.');
+        let mut path = Expr::Ident(Ident::new_no_ctxt(
+            segments.next().unwrap_or_default().into(),
+            DUMMY_SP,
+        ));
+
+        for segment in segments {
+            path = Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(path),
+                prop: MemberProp::Ident(IdentName::new(segment.into(), DUMMY_SP)),
+            });
+        }
+
+        let (value, context) = self.context(context).evaluate(Cow::Owned(path.clone()))?;
+
+        // The path evaluates to itself if it does not refer to anything.
+        if *value == path {
+            return Err(Error::UnknownBinding(name.to_string()));
+        }
+
+        T::deserialize(crate::Deserializer::resolved(value, context)?)
+    }
+
+    /// Deserialize the exports of a `CommonJS` module, such as a `babel.config.js` or
+    /// `tailwind.config.js` file.
+    ///
+    /// The exports are the value assigned to `module.exports`, merged with the properties assigned
+    /// to `exports` and `module.exports` (as in `exports.foo = ...`) in the order in which the
+    /// top-level statements make them. Assigning to `module.exports` replaces the properties that
+    /// were assigned before it. As in Node.js, properties of `exports` are ignored once either
+    /// `exports` or `module.exports` is reassigned, unless both are assigned the same value (as in
+    /// `module.exports = exports = { ... }`).
+    ///
+    /// ```
+    /// use ecmade::{Script, Value};
+    ///
+    /// let script =
+    ///     Script::parse("module.exports = { a: 1, b: 2 }; module.exports.c = 3; exports.b = 4;")?;
+    /// let exports: Value = script.exports()?;
+    ///
+    /// assert_eq!(exports["a"], Value::from(1));
+    /// assert_eq!(exports["b"], Value::from(2));
+    /// assert_eq!(exports["c"], Value::from(3));
+    /// # Ok::<(), ecmade::error::Error>(())
+    /// ```
+    pub fn exports<'de, T: serde::Deserialize<'de>>(&'de self) -> Result<T, Error>
+    where
+        'a: 'de,
+    {
+        self.exports_in(Context::with_options(self.options.clone()))
+    }
+
+    /// Deserialize the exports of a `CommonJS` module, as [`Self::exports`] does, also returning
+    /// the paths of all values ignored by the target type.
+    pub fn exports_with_ignored<'de, T: serde::Deserialize<'de>>(
+        &'de self,
+    ) -> Result<(T, Vec<Path>), Error>
+    where
+        'a: 'de,
+    {
+        let context = Context::collecting_ignored(self.options.clone());
+        let value = self.exports_in(context.clone())?;
+
+        Ok((value, context.take_ignored()))
+    }
+
+    fn exports_in<'de, T: serde::Deserialize<'de>>(
+        &'de self,
+        context: Context<'de>,
+    ) -> Result<T, Error>
+    where
+        'a: 'de,
+    {
+        let mut exports = Exports::default();
+
+        match &self.program {
+            Program::Script(script) => {
+                for stmt in &script.body {
+                    exports.stmt(stmt);
+                }
+            }
+            Program::Module(module) => {
+                for item in &module.body {
+                    if let ModuleItem::Stmt(stmt) = item {
+                        exports.stmt(stmt);
+                    }
+                }
+            }
+        }
+
+        let value = exports
+            .into_expr()
+            .ok_or_else(|| Error::UnknownBinding("module.exports".to_string()))?;
+
+        T::deserialize(crate::Deserializer::resolved(
+            Cow::Owned(value),
+            self.context(context),
+        )?)
+    }
+
+    #[must_use]
+    pub const fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// The context with the source of the script, in which its top-level bindings are resolved.
+    fn context<'de>(&'de self, mut context: Context<'de>) -> Context<'de>
+    where
+        'a: 'de,
+    {
+        if let Some(source) = self.source {
+            context = context.with_source(source);
+        }
+
+        context.with_scope(Scope::new(&self.program))
+    }
+}
+
+/// The assignments of a `CommonJS` module to `module.exports` and to its properties.
+#[derive(Default)]
+struct Exports<'s> {
+    /// The value last assigned to `module.exports`.
+    module: Option<&'s Expr>,
+    /// The values assigned to properties since then, in order.
+    properties: Vec<(&'s str, &'s Expr)>,
+    /// Whether `exports` was reassigned, so that it no longer refers to `module.exports`.
+    detached: bool,
+}
+
+impl<'s> Exports<'s> {
+    fn stmt(&mut self, stmt: &'s Stmt) {
+        if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &'s Expr) {
+        match expr {
+            Expr::Assign(assign) if assign.op == AssignOp::Assign => {
+                // Every target of a chained assignment such as `module.exports = exports = { ... }`
+                // is assigned the innermost value.
+                let mut targets = vec![&assign.left];
+                let mut assigned = &*assign.right;
+
+                while let Expr::Assign(assign) = assigned
+                    && assign.op == AssignOp::Assign
+                {
+                    targets.push(&assign.left);
+                    assigned = &assign.right;
+                }
+
+                // `exports` only still refers to the exports if both are assigned the same value.
+                let module = targets.iter().any(|target| {
+                    assign_target_name(target).is_some_and(|name| name == "module.exports")
+                });
+                let exports = targets.iter().any(|target| {
+                    matches!(
+                        target,
+                        AssignTarget::Simple(SimpleAssignTarget::Ident(ident))
+                            if ident.sym == "exports"
+                    )
+                });
+
+                for target in targets.into_iter().rev() {
+                    let member = match target {
+                        AssignTarget::Simple(SimpleAssignTarget::Ident(ident))
+                            if ident.sym == "exports" =>
+                        {
+                            self.detached = !module;
+                            continue;
+                        }
+                        AssignTarget::Simple(SimpleAssignTarget::Member(member)) => member,
+                        _ => continue,
+                    };
+
+                    match crate::member_variant(member) {
+                        Some((object, "exports")) if object == "module" => {
+                            self.module = Some(assigned);
+                            self.properties.clear();
+                            self.detached = !exports;
+                        }
+                        Some((object, property))
+                            if object == "module.exports"
+                                || (object == "exports" && !self.detached) =>
+                        {
+                            self.properties.push((property, assigned));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Expr::Seq(seq) => {
+                for expr in &seq.exprs {
+                    self.expr(expr);
+                }
+            }
+            Expr::Paren(paren) => self.expr(&paren.expr),
+            _ => {}
+        }
+    }
+
+    /// The merged exports, as `Object.assign(module.exports, { foo: ... }, ...)`, or `None` if
+    /// nothing was exported.
+    fn into_expr(self) -> Option<Expr> {
+        let module = self.module.cloned();
+
+        if self.properties.is_empty() {
+            return module;
+        }
+
+        let target = module.unwrap_or_else(|| {
+            Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: vec![],
+            })
+        });
+
+        let sources = self.properties.into_iter().map(|(property, value)| {
+            Expr::Object(ObjectLit {
+                span: value.span(),
+                props: vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Str(property.into()),
+                    value: Box::new(value.clone()),
+                })))],
+            })
+        });
+
+        Some(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: Callee::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(Ident::new_no_ctxt("Object".into(), DUMMY_SP))),
+                prop: MemberProp::Ident(IdentName::new("assign".into(), DUMMY_SP)),
+            }))),
+            args: std::iter::once(target)
+                .chain(sources)
+                .map(|expr| ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(expr),
+                })
+                .collect(),
+            ..CallExpr::default()
+        }))
+    }
+}
+
+/// The bindings of a script, or of the parameters and body of an immediately invoked function.
+#[derive(Debug, Default)]
+pub(crate) struct Scope<'a> {
+    bindings: HashMap<String, Binding<'a>>,
+    /// The scope that encloses this one, in which arguments are evaluated.
+    parent: Option<Rc<Self>>,
+}
+
+#[derive(Debug)]
+enum Binding<'a> {
+    /// Declared without a value, which is `undefined` unless it is assigned once.
+    Declared,
+    Value(Cow<'a, Expr>),
+    /// An argument of an immediately invoked function, which is evaluated in the parent scope.
+    Argument(Cow<'a, Expr>),
+    Reassigned,
+}
+
+/// The result of looking up a name in a scope.
+pub(crate) struct Lookup<'a> {
+    /// The scope in which the name is bound.
+    pub scope: Rc<Scope<'a>>,
+    pub value: Result<Cow<'a, Expr>, Error>,
+    /// The scope in which the value is evaluated.
+    pub value_scope: Option<Rc<Scope<'a>>>,
+}
+
+impl<'a> Scope<'a> {
+    pub(crate) fn new(program: &'a Program) -> Self {
+        let mut scope = Self::default();
+
+        match program {
+            Program::Script(script) => {
+                for stmt in &script.body {
+                    scope.stmt(stmt, &Cow::Borrowed);
+                }
+            }
+            Program::Module(module) => {
+                for item in &module.body {
+                    match item {
+                        ModuleItem::Stmt(stmt) => scope.stmt(stmt, &Cow::Borrowed),
+                        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => {
+                            if let Decl::Var(var) = &export.decl {
+                                scope.var(var, &Cow::Borrowed);
+                            }
+                        }
+                        ModuleItem::ModuleDecl(_) => {}
+                    }
+                }
+            }
+        }
+
+        scope
+    }
+
+    /// The value returned by an immediately invoked function, such as
+    /// `(function (a, b) { return [a, b]; })(1)`, and the scope in which it is evaluated.
+    ///
+    /// The parameters are bound to the arguments, and missing arguments are `undefined`. The body
+    /// may only contain declarations and assignments before the `return` statement. Expressions
+    /// of the function are converted to values with `value`, which either borrows or clones them.
+    ///
+    /// Returns `None` if the expression is not a call of a function that can be evaluated.
+    pub(crate) fn invoke<'s, F: Fn(&'s Expr) -> Cow<'a, Expr>>(
+        expr: &'s Expr,
+        parent: Option<Rc<Self>>,
+        value: &F,
+    ) -> Option<(Cow<'a, Expr>, Self)> {
+        let call = expr.unwrap_parens().as_call()?;
+
+        let (params, stmts, mut returned): (Vec<&Pat>, &[Stmt], _) = match call
+            .callee
+            .as_expr()?
+            .unwrap_parens()
+        {
+            Expr::Fn(FnExpr { function, .. }) if !function.is_async && !function.is_generator => (
+                function.params.iter().map(|param| &param.pat).collect(),
+                &function.body.as_ref()?.stmts,
+                None,
+            ),
+            Expr::Arrow(arrow) if !arrow.is_async && !arrow.is_generator => match &*arrow.body {
+                BlockStmtOrExpr::BlockStmt(block) => {
+                    (arrow.params.iter().collect(), &block.stmts, None)
+                }
+                BlockStmtOrExpr::Expr(expr) => {
+                    (arrow.params.iter().collect(), &[], Some(value(expr)))
+                }
+            },
+            _ => return None,
+        };
+
+        let mut scope = Self {
+            bindings: HashMap::new(),
+            parent,
+        };
+        let mut args = call.args.iter();
+
+        for param in params {
+            let Pat::Ident(ident) = param else {
+                return None;
+            };
+
+            let binding = match args.next() {
+                Some(ExprOrSpread { spread: None, expr }) => Binding::Argument(value(expr)),
+                Some(_) => return None,
+                None => Binding::Declared,
+            };
+
+            scope.bindings.insert(ident.sym.to_string(), binding);
+        }
+
+        for stmt in stmts {
+            match stmt {
+                Stmt::Return(ReturnStmt { arg, .. }) => {
+                    returned = arg.as_deref().map(value);
+                    break;
+                }
+                Stmt::Decl(Decl::Var(_)) | Stmt::Expr(_) | Stmt::Empty(_) => {
+                    scope.stmt(stmt, value);
+                }
+                _ => return None,
+            }
+        }
+
+        Some((returned.unwrap_or_else(|| Cow::Owned(undefined())), scope))
+    }
+
+    /// A copy of this scope and the scopes that enclose it that does not borrow the script.
+    pub(crate) fn to_static(&self) -> Scope<'static> {
+        Scope {
+            bindings: self
+                .bindings
+                .iter()
+                .map(|(name, binding)| {
+                    let binding = match binding {
+                        Binding::Declared => Binding::Declared,
+                        Binding::Value(value) => Binding::Value(Cow::Owned((**value).clone())),
+                        Binding::Argument(value) => {
+                            Binding::Argument(Cow::Owned((**value).clone()))
+                        }
+                        Binding::Reassigned => Binding::Reassigned,
+                    };
+
+                    (name.clone(), binding)
+                })
+                .collect(),
+            parent: self
+                .parent
+                .as_deref()
+                .map(|parent| Rc::new(parent.to_static())),
+        }
+    }
+
+    /// The value bound to a name in this scope or an enclosing one, or `None` if it is not bound.
+    pub(crate) fn lookup(self: &Rc<Self>, name: &str) -> Option<Lookup<'a>> {
+        let mut scope = self;
+
+        loop {
+            if let Some(binding) = scope.bindings.get(name) {
+                let (value, value_scope) = match binding {
+                    Binding::Declared => (Ok(Cow::Owned(undefined())), Some(scope.clone())),
+                    Binding::Value(value) => (Ok(value.clone()), Some(scope.clone())),
+                    Binding::Argument(value) => (Ok(value.clone()), scope.parent.clone()),
+                    Binding::Reassigned => (
+                        Err(Error::ReassignedBinding(name.to_string())),
+                        Some(scope.clone()),
+                    ),
+                };
+
+                return Some(Lookup {
+                    scope: scope.clone(),
+                    value,
+                    value_scope,
+                });
+            }
+
+            scope = scope.parent.as_ref()?;
+        }
+    }
+
+    fn stmt<'s, F: Fn(&'s Expr) -> Cow<'a, Expr>>(&mut self, stmt: &'s Stmt, value: &F) {
+        match stmt {
+            Stmt::Decl(Decl::Var(var)) => self.var(var, value),
+            Stmt::Expr(ExprStmt { expr, .. }) => self.expr(expr, value),
+            _ => {}
+        }
+    }
+
+    fn var<'s, F: Fn(&'s Expr) -> Cow<'a, Expr>>(&mut self, var: &'s VarDecl, value: &F) {
+        for declarator in &var.decls {
+            if let Pat::Ident(ident) = &declarator.name {
+                self.bind(ident.sym.to_string(), declarator.init.as_deref().map(value));
+            }
+        }
+    }
+
+    /// Record the assignments in an expression statement.
+    fn expr<'s, F: Fn(&'s Expr) -> Cow<'a, Expr>>(&mut self, expr: &'s Expr, value: &F) {
+        match expr {
+            Expr::Assign(AssignExpr {
+                op, left, right, ..
+            }) => {
+                if let Some(name) = assign_target_name(left) {
+                    if *op == AssignOp::Assign {
+                        // The value of a chained assignment such as `a = b = 1` is the innermost.
+                        let mut assigned = &**right;
+
+                        while let Expr::Assign(assign) = assigned
+                            && assign.op == AssignOp::Assign
+                        {
+                            assigned = &assign.right;
+                        }
+
+                        self.bind(name, Some(value(assigned)));
+                    } else {
+                        self.bindings.insert(name, Binding::Reassigned);
+                    }
+                }
+
+                self.expr(right, value);
+            }
+            Expr::Update(UpdateExpr { arg, .. }) => {
+                if let Expr::Ident(ident) = &**arg {
+                    self.bindings
+                        .insert(ident.sym.to_string(), Binding::Reassigned);
+                }
+            }
+            Expr::Seq(seq) => {
+                for expr in &seq.exprs {
+                    self.expr(expr, value);
+                }
+            }
+            Expr::Paren(paren) => self.expr(&paren.expr, value),
+            _ => {}
+        }
+    }
+
+    fn bind(&mut self, name: String, value: Option<Cow<'a, Expr>>) {
+        let binding = self.bindings.entry(name).or_insert(Binding::Declared);
+
+        if let Some(value) = value {
+            *binding = match binding {
+                Binding::Declared => Binding::Value(value),
+                Binding::Value(_) | Binding::Argument(_) | Binding::Reassigned => {
+                    Binding::Reassigned
+                }
+            };
+        }
+    }
+}
+
+/// The value of a property of an array, object, or string that is known statically, such as
+/// `items[0].name` or `items.length`, with the context in which it is evaluated. A dotted path
+/// such as `window.state` that was assigned to is resolved as a binding.
+///
+/// Expressions of the object and key are converted with `value`, which either borrows or clones
+/// them. Returns `None` if the expression is not a member expression or the property is not known.
+pub(crate) fn member<'s, 'a, F: Fn(&'s Expr) -> Cow<'a, Expr>>(
+    expr: &'s Expr,
+    context: &Context<'a>,
+    value: &F,
+) -> Option<Result<(Cow<'a, Expr>, Context<'a>), Error>> {
+    let member = expr.as_member()?;
+
+    if let Some((object, name)) = crate::member_variant(member)
+        && let Some(resolved) = context.resolve(&format!("{object}.{name}"))
+    {
+        return Some(resolved);
+    }
+
+    let key = match &member.prop {
+        MemberProp::Ident(ident) => ident.sym.to_string(),
+        MemberProp::Computed(ComputedPropName { expr, .. }) => {
+            match context.clone().evaluate(value(expr)) {
+                Ok((key, _)) => match &*key {
+                    Expr::Lit(Lit::Str(str)) => str.value.to_string(),
+                    Expr::Lit(Lit::Num(number)) => crate::number::to_js_string(number.value),
+                    _ => return None,
+                },
+                Err(error) => return Some(Err(error)),
+            }
+        }
+        MemberProp::PrivateName(_) => return None,
+    };
+
+    let (object, context) = match context.clone().evaluate(value(&member.obj)) {
+        Ok(evaluated) => evaluated,
+        Err(error) => return Some(Err(error)),
+    };
+
+    let property = match object {
+        Cow::Borrowed(object) => property(object, &key)?,
+        Cow::Owned(object) => Cow::Owned(property(&object, &key)?.into_owned()),
+    };
+
+    Some(Ok((property, context)))
+}
+
+/// The value of a property of an array, object, or string literal, or `None` if it is not known.
+#[allow(clippy::cast_precision_loss)]
+fn property<'o>(object: &'o Expr, key: &str) -> Option<Cow<'o, Expr>> {
+    let length = |length: usize| {
+        Cow::Owned(Expr::Lit(Lit::Num(Number {
+            span: DUMMY_SP,
+            value: length as f64,
+            raw: Some(length.to_string().into()),
+        })))
+    };
+    let index = key
+        .parse::<usize>()
+        .ok()
+        .filter(|index| index.to_string() == key);
+
+    match object {
+        Expr::Array(ArrayLit { elems, .. }) => {
+            if elems.iter().flatten().any(|elem| elem.spread.is_some()) {
+                return None;
+            }
+
+            if key == "length" {
+                return Some(length(elems.len()));
+            }
+
+            Some(match elems.get(index?) {
+                Some(Some(elem)) => Cow::Borrowed(&elem.expr),
+                Some(None) | None => Cow::Owned(undefined()),
+            })
+        }
+        Expr::Object(ObjectLit { props, .. }) => {
+            // The last property with the key is the one that is defined.
+            for prop in props.iter().rev() {
+                let PropOrSpread::Prop(prop) = prop else {
+                    return None;
+                };
+
+                match &**prop {
+                    Prop::KeyValue(KeyValueProp { key: name, value }) => {
+                        let matches = match name {
+                            PropName::Ident(ident) => ident.sym == key,
+                            PropName::Str(str) => str.value == key,
+                            PropName::Num(number) => {
+                                crate::number::to_js_string(number.value) == key
+                            }
+                            _ => return None,
+                        };
+
+                        if matches {
+                            return Some(Cow::Borrowed(value));
+                        }
+                    }
+                    Prop::Shorthand(ident) if ident.sym == key => {
+                        return Some(Cow::Owned(Expr::Ident(ident.clone())));
+                    }
+                    Prop::Shorthand(_) => {}
+                    _ => return None,
+                }
+            }
+
+            Some(Cow::Owned(undefined()))
+        }
+        Expr::Lit(Lit::Str(str)) => {
+            if key == "length" {
+                return Some(length(str.value.encode_utf16().count()));
+            }
+
+            let Some(unit) = str.value.encode_utf16().nth(index?) else {
+                return Some(Cow::Owned(undefined()));
+            };
+
+            // Half of a surrogate pair cannot be represented.
+            let char = String::from_utf16(&[unit]).ok()?;
+
+            Some(Cow::Owned(Expr::Lit(Lit::Str(char.as_str().into()))))
+        }
+        _ => None,
+    }
+}
+
+/// When folding constants is enabled, the operand chosen by a conditional or logical operator
+/// whose condition is known once identifiers are resolved, such as
+/// `process.env.API_URL ?? "default"` or `debug ? 1 : 0`, with the context in which it is
+/// evaluated. Other operators whose operands resolve to literals are also evaluated.
+///
+/// Expressions of the operator are converted with `value`, which either borrows or clones them.
+/// Returns `None` if folding is disabled, the expression is not such an operator, or the condition
+/// is not known.
+pub(crate) fn choose<'s, 'a, F: Fn(&'s Expr) -> Cow<'a, Expr>>(
+    expr: &'s Expr,
+    context: &Context<'a>,
+    value: &F,
+) -> Option<Result<(Cow<'a, Expr>, Context<'a>), Error>> {
+    if !context.options().fold_constants {
+        return None;
+    }
+
+    let chosen = match expr {
+        Expr::Cond(cond) => context
+            .clone()
+            .evaluate(value(cond.test.unwrap_parens()))
+            .and_then(|(test, test_context)| operand(&test, &test_context))
+            .map(|test| {
+                test.map(|test| {
+                    let operand = if test.is_truthy() {
+                        &cond.cons
+                    } else {
+                        &cond.alt
+                    };
+
+                    (value(operand.unwrap_parens()), context.clone())
+                })
+            }),
+        Expr::Bin(bin)
+            if matches!(
+                bin.op,
+                BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing
+            ) =>
+        {
+            context
+                .clone()
+                .evaluate(value(bin.left.unwrap_parens()))
+                .and_then(|(left, left_context)| {
+                    Ok(operand(&left, &left_context)?.map(|operand| {
+                        let short_circuits = match bin.op {
+                            BinaryOp::LogicalAnd => !operand.is_truthy(),
+                            BinaryOp::LogicalOr => operand.is_truthy(),
+                            _ => !operand.is_nullish(),
+                        };
+
+                        if !short_circuits {
+                            (value(bin.right.unwrap_parens()), context.clone())
+                        } else if let Operand::Constant(constant) = operand
+                            && Constant::from_expr(&left).is_none()
+                        {
+                            // The operand is an expression such as `!a` that has been evaluated.
+                            (Cow::Owned(constant.into_expr(left.span())), left_context)
+                        } else {
+                            (left, left_context)
+                        }
+                    }))
+                })
+        }
+        Expr::Bin(_) | Expr::Unary(_) => constant(expr, context).map(|constant| {
+            constant.map(|constant| (Cow::Owned(constant.into_expr(expr.span())), context.clone()))
+        }),
+        Expr::Paren(paren) => Ok(Some((value(&paren.expr), context.clone()))),
+        // Every expression but the last is evaluated only for its effects, so it must be free of
+        // them.
+        Expr::Seq(seq) => {
+            let (last, rest) = seq.exprs.split_last()?;
+
+            context.fold_step().and_then(|context| {
+                for item in rest {
+                    let (item, item_context) = context.clone().evaluate(value(item))?;
+
+                    if constant(&item, &item_context)?.is_none() {
+                        return Ok(None);
+                    }
+                }
+
+                Ok(Some((value(last), context)))
+            })
+        }
+        _ => return None,
+    };
+
+    chosen.transpose()
+}
+
+/// An evaluated operand of a conditional or logical operator whose truthiness is known.
+enum Operand {
+    /// An object, array, function, or regular expression, which is always truthy.
+    Object,
+    Constant(Constant),
+}
+
+impl Operand {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Self::Object => true,
+            Self::Constant(constant) => constant.to_bool(),
+        }
+    }
+
+    const fn is_nullish(&self) -> bool {
+        matches!(self, Self::Constant(Constant::Undefined | Constant::Null))
+    }
+}
+
+fn operand(expr: &Expr, context: &Context<'_>) -> Result<Option<Operand>, Error> {
+    Ok(match expr {
+        Expr::Object(_)
+        | Expr::Array(_)
+        | Expr::Fn(_)
+        | Expr::Arrow(_)
+        | Expr::Lit(Lit::Regex(_)) => Some(Operand::Object),
+        other => constant(other, context)?.map(Operand::Constant),
+    })
+}
+
+/// The primitive value of an evaluated expression, applying operators to operands that resolve
+/// to literals.
+fn constant(expr: &Expr, context: &Context<'_>) -> Result<Option<Constant>, Error> {
+    let operand = |expr: &Expr, context: &Context<'_>| match expr.unwrap_parens() {
+        // Operators other than logical ones need not be evaluated first, which also keeps long
+        // chains of them from taking as much of the stack.
+        expr @ Expr::Unary(_) => constant(expr, context),
+        Expr::Bin(bin)
+            if !matches!(
+                bin.op,
+                BinaryOp::LogicalAnd | BinaryOp::LogicalOr | BinaryOp::NullishCoalescing
+            ) =>
+        {
+            constant(expr.unwrap_parens(), context)
+        }
+        expr => {
+            let (operand, context) = context.clone().evaluate(Cow::Owned(expr.clone()))?;
+
+            constant(&operand, &context)
+        }
+    };
+
+    Ok(match expr {
+        Expr::Bin(bin) => {
+            let context = context.fold_step()?;
+
+            match (
+                operand(&bin.left, &context)?,
+                operand(&bin.right, &context)?,
+            ) {
+                (Some(left), Some(right)) => crate::fold::binary(bin.op, left, right),
+                _ => None,
+            }
+        }
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::TypeOf,
+            arg,
+            ..
+        }) => type_of(arg, &context.fold_step()?)?
+            .map(|type_of| Constant::String(type_of.to_string())),
+        Expr::Unary(unary) => operand(&unary.arg, &context.fold_step()?)?
+            .and_then(|arg| crate::fold::unary(unary.op, arg)),
+        other => Constant::from_expr(other),
+    })
+}
+
+/// The result of `typeof`, which is also defined for functions and objects.
+fn type_of(arg: &Expr, context: &Context<'_>) -> Result<Option<&'static str>, Error> {
+    let (arg, context) = context
+        .clone()
+        .evaluate(Cow::Owned(arg.unwrap_parens().clone()))?;
+
+    Ok(match &*arg {
+        Expr::Object(_) | Expr::Array(_) | Expr::Lit(Lit::Regex(_)) => Some("object"),
+        Expr::Fn(_) | Expr::Arrow(_) => Some("function"),
+        // Identifiers with values have been resolved, but a declared function or class has not,
+        // and one that is not bound may be a global of the host, such as `module` or `window`.
+        Expr::Ident(_) if Constant::from_expr(&arg).is_none() => None,
+        other => constant(other, &context)?.map(|constant| constant.type_of()),
+    })
+}
+
+fn undefined() -> Expr {
+    Expr::Ident(Ident::new_no_ctxt("undefined".into(), DUMMY_SP))
+}
+
+/// The name of an identifier or a dotted path such as `window.state` that is assigned to.
+fn assign_target_name(target: &AssignTarget) -> Option<String> {
+    match target {
+        AssignTarget::Simple(SimpleAssignTarget::Ident(ident)) => Some(ident.sym.to_string()),
+        AssignTarget::Simple(SimpleAssignTarget::Member(member)) => {
+            crate::member_variant(member).map(|(object, property)| format!("{object}.{property}"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Script;
+    use crate::error::Error;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct State<'a> {
+        foo: &'a str,
+        bar: Vec<u8>,
+        point: Point,
+        later: &'a str,
+        missing: Option<u8>,
+        nested: Vec<Point>,
+    }
+
+    #[test]
+    fn resolve_bindings() -> Result<(), Error> {
+        let script = Script::parse(
+            r#"
+            var a = "x", b = [1, 2];
+            const point = { x: 1, y: 2 };
+            let later, missing;
+            later = "set";
+            window.state = { foo: a, bar: b, point, later, missing, nested: [point, copy] };
+            var copy = point;
+            var self = { me: self };
+            var p = q, q = p;
+            var x = 1;
+            x = 2;
+            var n = 0;
+            n++;
+            window["other"] = window.more = a;
+            "#,
+        )?;
+
+        assert_eq!(
+            script.get::<State<'_>>("window.state")?,
+            State {
+                foo: "x",
+                bar: vec![1, 2],
+                point: Point { x: 1, y: 2 },
+                later: "set",
+                missing: None,
+                nested: vec![Point { x: 1, y: 2 }, Point { x: 1, y: 2 }],
+            }
+        );
+        assert_eq!(script.get::<&str>("window.other")?, "x");
+        assert_eq!(script.get::<&str>("window.more")?, "x");
+        assert_eq!(
+            script.get::<crate::Value>("copy")?,
+            crate::js!({ x: 1, y: 2 })
+        );
+
+        assert!(matches!(
+            script.get::<crate::Value>("self"),
+            Err(Error::CyclicBinding(name)) if name == "self"
+        ));
+        assert!(matches!(
+            script.get::<u8>("p"),
+            Err(Error::CyclicBinding(name)) if name == "p"
+        ));
+        assert!(matches!(
+            script.get::<u8>("x"),
+            Err(Error::ReassignedBinding(name)) if name == "x"
+        ));
+        assert!(matches!(
+            script.get::<u8>("n"),
+            Err(Error::ReassignedBinding(name)) if name == "n"
+        ));
+        assert!(matches!(
+            script.get::<u8>("y"),
+            Err(Error::UnknownBinding(name)) if name == "y"
+        ));
+
+        let module = crate::Options::new()
+            .fold_constants(true)
+            .parse_script("const ttl = 60 * 60; export const config = { ttl };")?;
+
+        assert_eq!(
+            module.get::<std::collections::HashMap<String, u32>>("config")?,
+            std::iter::once(("ttl".to_string(), 3600)).collect()
+        );
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Nuxt {
+        data: Vec<Datum>,
+        count: u8,
+        missing: Option<u8>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Datum {
+        x: String,
+        y: Option<String>,
+    }
+
+    #[test]
+    fn invoke_functions() -> Result<(), Error> {
+        let payload = r#"(function(a,b,c,d){return {data:[{x:a,y:b},{x:"bar",y:a}],count:c,missing:d}}("foo",null,3))"#;
+        let expected = Nuxt {
+            data: vec![
+                Datum {
+                    x: "foo".to_string(),
+                    y: None,
+                },
+                Datum {
+                    x: "bar".to_string(),
+                    y: Some("foo".to_string()),
+                },
+            ],
+            count: 3,
+            missing: None,
+        };
+
+        assert_eq!(crate::from_str::<Nuxt>(payload)?, expected);
+
+        let source = format!("window.__NUXT__={payload};");
+        let script = Script::parse(&source)?;
+
+        assert_eq!(script.get::<Nuxt>("window.__NUXT__")?, expected);
+
+        let script = Script::parse(
+            r#"
+            var a = "outer";
+            var shadowed = (function (a, b) { return [a, b]; })(a, a);
+            var arrow = ((x) => ({ x }))(1);
+            var body = (() => { "use strict"; var c = a; return { c }; })();
+            var nested = (function (a) { return (function (b) { return [a, b]; })(a); })(2);
+            var control = (function () { if (a) { return 1; } })();
+            "#,
+        )?;
+
+        assert_eq!(script.get::<[&str; 2]>("shadowed")?, ["outer", "outer"]);
+        assert_eq!(script.get::<crate::Value>("arrow")?, crate::js!({ x: 1 }));
+        assert_eq!(
+            script.get::<crate::Value>("body")?,
+            crate::js!({ c: "outer" })
+        );
+        assert_eq!(script.get::<[u8; 2]>("nested")?, [2, 2]);
+        assert!(script.get::<crate::Value>("control").is_err());
+
+        assert_eq!(
+            crate::from_str::<crate::Value>("(function (a, b) { return [a, b]; })(1)")?,
+            crate::js!([1, undefined])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn member_access() -> Result<(), Error> {
+        let script = Script::parse(
+            r#"
+            var S = ["a", "b", "c"];
+            var _0x = ["foo", "bar"];
+            var CONFIG = { items: [{ name: "first" }], "the key": 1, 0: "zero", dup: 1, dup: 2 };
+            var i = 1;
+            window.state = { point: { x: 1, y: 2 } };
+            var data = {
+                x: S[2],
+                y: CONFIG.items[0].name,
+                n: S.length,
+                l: "h\u00e9llo".length,
+                c: "abc"[i],
+                k: CONFIG["the key"],
+                z: CONFIG[0],
+                d: CONFIG.dup,
+                h: _0x[0x1],
+                u: S[5],
+                m: CONFIG.missing,
+                w: window.state.point.y,
+            };
+            var unknown = { r: document.title };
+            "#,
+        )?;
+
+        assert_eq!(
+            script.get::<crate::Value>("data")?,
+            crate::js!({
+                x: "c",
+                y: "first",
+                n: 3,
+                l: 5,
+                c: "b",
+                k: 1,
+                z: "zero",
+                d: 2,
+                h: "bar",
+                u: undefined,
+                m: undefined,
+                w: 2,
+            })
+        );
+        assert_eq!(script.get::<&str>("data.x")?, "c");
+        assert!(script.get::<crate::Value>("unknown").is_err());
+
+        assert_eq!(
+            crate::from_str::<String>(r#"(function (a) { return a[1]; })(["p", "q"])"#)?,
+            "q"
+        );
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Deployment {
+        env: String,
+        url: String,
+        debug: bool,
+        replicas: u8,
+        region: String,
+        features: Vec<String>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Defaults {
+        region: &'static str,
+        features: [&'static str; 2],
+    }
+
+    #[test]
+    fn environment() -> Result<(), Error> {
+        let source = r#"
+            const env = process.env.NODE_ENV || "development";
+            const production = env === "production";
+            module.exports = {
+                env,
+                url: process.env.API_URL ?? (production ? "https://example.com" : "http://localhost"),
+                debug: !production && DEBUG,
+                replicas: production ? REPLICAS : 1,
+                region: DEFAULTS.region,
+                features: DEFAULTS.features,
+            };
+        "#;
+
+        let options = crate::Options::new()
+            .fold_constants(true)
+            .global("DEBUG", true)
+            .global("REPLICAS", 3)
+            .global_serialized(
+                "DEFAULTS",
+                &Defaults {
+                    region: "eu",
+                    features: ["a", "b"],
+                },
+            )?;
+
+        let deployment = |options: crate::Options| -> Result<Deployment, Error> {
+            options.parse_script(source)?.get("module.exports")
+        };
+
+        assert_eq!(
+            deployment(options.clone().env([("HOME", "/home/web")]))?,
+            Deployment {
+                env: "development".to_string(),
+                url: "http://localhost".to_string(),
+                debug: true,
+                replicas: 1,
+                region: "eu".to_string(),
+                features: vec!["a".to_string(), "b".to_string()],
+            }
+        );
+        assert_eq!(
+            deployment(options.clone().env([("NODE_ENV", "production")]))?,
+            Deployment {
+                env: "production".to_string(),
+                url: "https://example.com".to_string(),
+                debug: false,
+                replicas: 3,
+                region: "eu".to_string(),
+                features: vec!["a".to_string(), "b".to_string()],
+            }
+        );
+        assert_eq!(
+            deployment(
+                options
+                    .clone()
+                    .env([("NODE_ENV", "staging"), ("API_URL", "https://staging")])
+            )?
+            .url,
+            "https://staging"
+        );
+
+        assert_eq!(
+            crate::Options::new()
+                .fold_constants(true)
+                .env([("HOST", "example.com")])
+                .from_str::<String>(r#""https://" + process.env.HOST"#)?,
+            "https://example.com"
+        );
+        assert!(
+            crate::Options::new()
+                .fold_constants(true)
+                .from_str::<String>("UNKNOWN ? 'a' : 'b'")
+                .is_err()
+        );
+        assert!(
+            crate::Options::new()
+                .env([("NODE_ENV", "production")])
+                .from_str::<String>("process.env.NODE_ENV || 'development'")
+                .is_err()
+        );
+
+        // Without an environment, its variables are unknown rather than `undefined`.
+        assert!(deployment(options).is_err());
+        assert!(crate::from_str::<Option<String>>("process.env.HOME").is_err());
+        assert_eq!(
+            crate::Options::new()
+                .env([("HOME", "/home/web")])
+                .from_str::<Option<String>>("process.env.PATH")?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Babel {
+        presets: Vec<String>,
+        plugins: Vec<String>,
+        env: Option<crate::Value>,
+        #[serde(rename = "sourceMaps")]
+        source_maps: bool,
+    }
+
+    #[test]
+    fn commonjs_exports() -> Result<(), Error> {
+        let babel = r#"
+            "use strict";
+            const presets = ["@babel/preset-env"];
+            exports.ignored = true;
+            module.exports = { presets, plugins: [], sourceMaps: false };
+            module.exports.plugins = ["macros"];
+            exports.sourceMaps = true;
+        "#;
+
+        assert_eq!(
+            crate::from_commonjs_str::<Babel>(babel)?,
+            Babel {
+                presets: vec!["@babel/preset-env".to_string()],
+                plugins: vec!["macros".to_string()],
+                env: None,
+                // `exports` no longer refers to the exports once `module.exports` is assigned.
+                source_maps: false,
+            }
+        );
+
+        assert_eq!(
+            crate::from_commonjs_str::<crate::Value>(
+                r#"exports.a = 1; exports["b"] = exports.c = "x"; exports.a = 2;"#
+            )?,
+            crate::js!({ a: 2, c: "x", b: "x" })
+        );
+        assert_eq!(
+            crate::from_commonjs_str::<Vec<u8>>("module.exports = exports = [1, 2];")?,
+            [1, 2]
+        );
+        assert_eq!(
+            crate::from_commonjs_str::<crate::Value>(
+                "exports.a = 1; exports = { z: 1 }; exports.c = 3; module.exports.d = 4;"
+            )?,
+            crate::js!({ a: 1, d: 4 })
+        );
+        assert_eq!(
+            crate::from_commonjs_str::<crate::Value>(
+                "exports = { z: 1 }; module.exports = exports = { a: 1 }; exports.b = 2;"
+            )?,
+            crate::js!({ a: 1, b: 2 })
+        );
+        assert_eq!(
+            crate::from_commonjs_str::<crate::Value>(
+                "module.exports = { a: 1 }; exports.b = 2; module.exports.c = 3;"
+            )?,
+            crate::js!({ a: 1, c: 3 })
+        );
+        assert!(matches!(
+            crate::from_commonjs_str::<crate::Value>("var config = {};"),
+            Err(Error::UnknownBinding(_))
+        ));
+
+        Ok(())
+    }
+}
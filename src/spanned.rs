@@ -0,0 +1,203 @@
+use serde::de::{
+    Deserialize, DeserializeSeed, Deserializer, Error as _, IntoDeserializer, MapAccess, Visitor,
+    value::BorrowedStrDeserializer,
+};
+use std::ops::Range;
+use swc_common::Spanned as _;
+
+pub(crate) const NAME: &str = "$__ecmade_private_Spanned";
+pub(crate) const START: &str = "$__ecmade_private_start";
+pub(crate) const END: &str = "$__ecmade_private_end";
+pub(crate) const START_LINE: &str = "$__ecmade_private_start_line";
+pub(crate) const START_COLUMN: &str = "$__ecmade_private_start_column";
+pub(crate) const END_LINE: &str = "$__ecmade_private_end_line";
+pub(crate) const END_COLUMN: &str = "$__ecmade_private_end_column";
+pub(crate) const VALUE: &str = "$__ecmade_private_value";
+pub(crate) const FIELDS: &[&str] = &[
+    START,
+    END,
+    START_LINE,
+    START_COLUMN,
+    END_LINE,
+    END_COLUMN,
+    VALUE,
+];
+
+/// A deserialized value together with the location of the JavaScript expression it came from.
+///
+/// Byte offsets are taken from the spans of the parsed expression. Lines and columns are only
+/// available when the source text is known (for example when using [`crate::from_str`]).
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    span: Range<usize>,
+    start: Option<LineColumn>,
+    end: Option<LineColumn>,
+    value: T,
+}
+
+/// A one-based line number and a zero-based column counted in characters.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    /// Lines are passed to the visitor as zero when the source is not available.
+    const fn from_parts(line: usize, column: usize) -> Option<Self> {
+        if line == 0 {
+            None
+        } else {
+            Some(Self { line, column })
+        }
+    }
+}
+
+impl<T> Spanned<T> {
+    /// The byte range of the expression.
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    #[must_use]
+    pub const fn start(&self) -> Option<LineColumn> {
+        self.start
+    }
+
+    #[must_use]
+    pub const fn end(&self) -> Option<LineColumn> {
+        self.end
+    }
+
+    pub const fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    pub const fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T: serde::Serialize> serde::Serialize for Spanned<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct(NAME, FIELDS, SpannedVisitor(std::marker::PhantomData))
+    }
+}
+
+struct SpannedVisitor<T>(std::marker::PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for SpannedVisitor<T> {
+    type Value = Spanned<T>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a spanned value")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let start = next_entry::<A, usize>(&mut map, START)?;
+        let end = next_entry::<A, usize>(&mut map, END)?;
+        let start_line = next_entry::<A, usize>(&mut map, START_LINE)?;
+        let start_column = next_entry::<A, usize>(&mut map, START_COLUMN)?;
+        let end_line = next_entry::<A, usize>(&mut map, END_LINE)?;
+        let end_column = next_entry::<A, usize>(&mut map, END_COLUMN)?;
+        let value = next_entry::<A, T>(&mut map, VALUE)?;
+
+        Ok(Spanned {
+            span: start..end,
+            start: LineColumn::from_parts(start_line, start_column),
+            end: LineColumn::from_parts(end_line, end_column),
+            value,
+        })
+    }
+}
+
+fn next_entry<'de, A: MapAccess<'de>, T: Deserialize<'de>>(
+    map: &mut A,
+    expected: &'static str,
+) -> Result<T, A::Error> {
+    match map.next_key::<&str>()? {
+        Some(key) if key == expected => map.next_value(),
+        _ => Err(A::Error::custom(
+            "Spanned can only be deserialized by ecmade",
+        )),
+    }
+}
+
+/// Provides the location fields followed by the value to the visitor for [`Spanned`].
+pub(crate) struct SpannedMap<'de> {
+    location: [usize; 6],
+    index: usize,
+    deserializer: Option<crate::Deserializer<'de>>,
+}
+
+impl<'de> SpannedMap<'de> {
+    pub(crate) fn new(deserializer: crate::Deserializer<'de>) -> Self {
+        let span = deserializer.expr.span();
+        let start = deserializer.context.line_column(span.lo);
+        let end = deserializer.context.line_column(span.hi);
+
+        Self {
+            location: [
+                span.lo.0 as usize,
+                span.hi.0 as usize,
+                start.map_or(0, |start| start.line),
+                start.map_or(0, |start| start.column),
+                end.map_or(0, |end| end.line),
+                end.map_or(0, |end| end.column),
+            ],
+            index: 0,
+            deserializer: Some(deserializer),
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for SpannedMap<'de> {
+    type Error = crate::error::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        FIELDS
+            .get(self.index)
+            .map(|field| seed.deserialize(BorrowedStrDeserializer::new(field)))
+            .transpose()
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let index = self.index;
+        self.index += 1;
+
+        match self.location.get(index) {
+            Some(value) => seed.deserialize(value.into_deserializer()),
+            None => self
+                .deserializer
+                .take()
+                .map_or(Err(Self::Error::ExpectedFieldValue), |deserializer| {
+                    seed.deserialize(deserializer)
+                }),
+        }
+    }
+}
@@ -0,0 +1,1080 @@
+use crate::context::Context;
+use crate::error::Error;
+use crate::regex::Regex;
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use serde::de::{
+    Deserialize, Deserializer, Error as _, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use swc_common::DUMMY_SP;
+use swc_ecma_ast::{
+    ArrayLit, BigIntValue, Expr, ExprOrSpread, Ident, KeyValueProp, Lit, ObjectLit, Prop, PropName,
+    PropOrSpread, UnaryExpr, UnaryOp,
+};
+
+mod ser;
+
+pub use ser::to_value;
+
+pub(crate) const NAME: &str = "$__ecmade_private_Value";
+
+/// The keys of the maps with a single entry that encode values which serde's data model cannot
+/// represent directly.
+const UNDEFINED_KEY: &str = "$__ecmade_private_Value_undefined";
+const HOLE_KEY: &str = "$__ecmade_private_Value_hole";
+const BIG_INT_KEY: &str = "$__ecmade_private_Value_big_int";
+const REGEX_KEY: &str = "$__ecmade_private_Value_regex";
+
+/// Any JavaScript literal value.
+///
+/// Unlike `serde_json::Value`, this can represent `undefined`, array holes, big integers, regular
+/// expressions, non-finite numbers, and the difference between integer and floating-point literals.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Value {
+    #[default]
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(Number),
+    BigInt(BigIntValue),
+    String(String),
+    Regex(Regex),
+    Array(Vec<Self>),
+    Object(Object),
+    /// A missing element in an array literal, such as the second element of `[1, , 3]`.
+    Hole,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    /// A number written without a fractional part or exponent that fits in an `i64`.
+    Integer(i64),
+    Float(f64),
+}
+
+/// The properties of an object, in the order in which they were first defined.
+#[derive(Clone, Default)]
+pub struct Object {
+    properties: Vec<(String, Value)>,
+    /// The position of each property, so that it can be found without scanning the others.
+    positions: HashMap<String, usize>,
+}
+
+/// How to convert values that JSON cannot represent.
+///
+/// This applies to `undefined`, holes, non-finite numbers, regular expressions, and big integers
+/// that do not fit in a 64-bit integer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum JsonPolicy {
+    /// Replace the value with `null`.
+    #[default]
+    Null,
+    /// Replace the value with a string containing its JavaScript representation.
+    String,
+    /// Fail with [`Error::UnrepresentableJson`].
+    Error,
+}
+
+static UNDEFINED: Value = Value::Undefined;
+
+impl Value {
+    /// Convert a literal expression into a value.
+    pub fn from_expr(expr: &Expr) -> Result<Self, Error> {
+        crate::from_expr(expr)
+    }
+
+    /// Convert this value into an equivalent expression.
+    #[must_use]
+    pub fn to_expr(&self) -> Expr {
+        match self {
+            Self::Undefined | Self::Hole => ident_expr("undefined"),
+            Self::Null => Expr::Lit(Lit::Null(swc_ecma_ast::Null { span: DUMMY_SP })),
+            Self::Bool(value) => Expr::Lit(Lit::Bool(swc_ecma_ast::Bool {
+                span: DUMMY_SP,
+                value: *value,
+            })),
+            Self::Number(Number::Integer(value)) => Expr::Lit(Lit::Num(swc_ecma_ast::Number {
+                span: DUMMY_SP,
+                #[allow(clippy::cast_precision_loss)]
+                value: *value as f64,
+                raw: Some(value.to_string().into()),
+            })),
+            Self::Number(Number::Float(value)) => Expr::Lit(Lit::Num(swc_ecma_ast::Number {
+                span: DUMMY_SP,
+                value: *value,
+                raw: None,
+            })),
+            Self::BigInt(value) => Expr::Lit(Lit::BigInt(swc_ecma_ast::BigInt {
+                span: DUMMY_SP,
+                value: Box::new(value.clone()),
+                raw: None,
+            })),
+            Self::String(value) => Expr::Lit(Lit::Str(value.as_str().into())),
+            Self::Regex(regex) => Expr::Lit(Lit::Regex(swc_ecma_ast::Regex {
+                span: DUMMY_SP,
+                exp: regex.pattern.as_str().into(),
+                flags: regex.flags.as_str().into(),
+            })),
+            Self::Array(values) => Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: values
+                    .iter()
+                    .map(|value| match value {
+                        Self::Hole => None,
+                        other => Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(other.to_expr()),
+                        }),
+                    })
+                    .collect(),
+            }),
+            Self::Object(object) => Expr::Object(ObjectLit {
+                span: DUMMY_SP,
+                props: object
+                    .iter()
+                    .map(|(key, value)| {
+                        PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                            key: PropName::Str(key.as_str().into()),
+                            value: Box::new(value.to_expr()),
+                        })))
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_undefined(&self) -> bool {
+        matches!(self, Self::Undefined)
+    }
+
+    #[must_use]
+    pub const fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    #[must_use]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(number) => Some(number.as_f64()),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Number(Number::Integer(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_object(&self) -> Option<&Object> {
+        match self {
+            Self::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Convert to JSON, using the policy for values that JSON cannot represent.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self, policy: JsonPolicy) -> Result<serde_json::Value, Error> {
+        let unrepresentable = |representation: String| match policy {
+            JsonPolicy::Null => Ok(serde_json::Value::Null),
+            JsonPolicy::String => Ok(serde_json::Value::String(representation)),
+            JsonPolicy::Error => Err(Error::UnrepresentableJson(Box::new(self.clone()))),
+        };
+
+        match self {
+            Self::Undefined | Self::Hole => unrepresentable("undefined".to_string()),
+            Self::Null => Ok(serde_json::Value::Null),
+            Self::Bool(value) => Ok(serde_json::Value::Bool(*value)),
+            Self::Number(Number::Integer(value)) => Ok((*value).into()),
+            Self::Number(Number::Float(value)) => serde_json::Number::from_f64(*value).map_or_else(
+                || unrepresentable(crate::number::to_js_string(*value)),
+                |number| Ok(serde_json::Value::Number(number)),
+            ),
+            Self::BigInt(value) => i64::try_from(value)
+                .map(serde_json::Value::from)
+                .or_else(|_| u64::try_from(value).map(serde_json::Value::from))
+                .or_else(|_| unrepresentable(format!("{value}n"))),
+            Self::String(value) => Ok(serde_json::Value::String(value.clone())),
+            Self::Regex(regex) => unrepresentable(regex.to_string()),
+            Self::Array(values) => values
+                .iter()
+                .map(|value| value.to_json(policy))
+                .collect::<Result<_, _>>()
+                .map(serde_json::Value::Array),
+            Self::Object(object) => object
+                .iter()
+                .map(|(key, value)| value.to_json(policy).map(|value| (key.clone(), value)))
+                .collect::<Result<_, _>>()
+                .map(serde_json::Value::Object),
+        }
+    }
+}
+
+impl Number {
+    #[must_use]
+    pub const fn as_f64(self) -> f64 {
+        match self {
+            #[allow(clippy::cast_precision_loss)]
+            Self::Integer(value) => value as f64,
+            Self::Float(value) => value,
+        }
+    }
+}
+
+impl Object {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.positions
+            .get(key)
+            .map(|&position| &self.properties[position].1)
+    }
+
+    /// Set a property, keeping its original position if it was already defined.
+    pub fn insert(&mut self, key: String, value: Value) -> Option<Value> {
+        if let Some(&position) = self.positions.get(&key) {
+            Some(std::mem::replace(&mut self.properties[position].1, value))
+        } else {
+            self.positions.insert(key.clone(), self.properties.len());
+            self.properties.push((key, value));
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.properties.iter().map(|(key, value)| (key, value))
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Self) -> bool {
+        self.properties == other.properties
+    }
+}
+
+impl std::fmt::Debug for Object {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<(String, Value)> for Object {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        let mut object = Self::new();
+
+        for (key, value) in iter {
+            object.insert(key, value);
+        }
+
+        object
+    }
+}
+
+impl IntoIterator for Object {
+    type Item = (String, Value);
+    type IntoIter = std::vec::IntoIter<(String, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.properties.into_iter()
+    }
+}
+
+impl std::ops::Index<usize> for Value {
+    type Output = Self;
+
+    /// Missing elements (and indexing into non-arrays) give `undefined`, as in JavaScript.
+    fn index(&self, index: usize) -> &Self::Output {
+        match self {
+            Self::Array(values) => values.get(index).unwrap_or(&UNDEFINED),
+            _ => &UNDEFINED,
+        }
+    }
+}
+
+impl std::ops::Index<&str> for Value {
+    type Output = Self;
+
+    /// Missing properties (and indexing into non-objects) give `undefined`, as in JavaScript.
+    fn index(&self, key: &str) -> &Self::Output {
+        match self {
+            Self::Object(object) => object.get(key).unwrap_or(&UNDEFINED),
+            _ => &UNDEFINED,
+        }
+    }
+}
+
+macro_rules! from_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Self::Number(Number::Integer(value.into()))
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or_else(
+            #[allow(clippy::cast_precision_loss)]
+            |_| Self::Number(Number::Float(value as f64)),
+            |value| Self::Number(Number::Integer(value)),
+        )
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::Number(Number::Float(value.into()))
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Number(Number::Float(value))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<BigIntValue> for Value {
+    fn from(value: BigIntValue) -> Self {
+        Self::BigInt(value)
+    }
+}
+
+impl From<Object> for Value {
+    fn from(value: Object) -> Self {
+        Self::Object(value)
+    }
+}
+
+impl<T: Into<Self>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Self>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Self::Null,
+            serde_json::Value::Bool(value) => Self::Bool(value),
+            serde_json::Value::Number(number) => number.as_i64().map_or_else(
+                || Self::Number(Number::Float(number.as_f64().unwrap_or(f64::NAN))),
+                |value| Self::Number(Number::Integer(value)),
+            ),
+            serde_json::Value::String(value) => Self::String(value),
+            serde_json::Value::Array(values) => {
+                Self::Array(values.into_iter().map(Into::into).collect())
+            }
+            serde_json::Value::Object(object) => Self::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl serde::Serialize for Value {
+    /// Values that other formats cannot represent are serialized as their closest equivalents:
+    /// `undefined` and holes as unit, regular expressions as strings, and big integers as
+    /// 128-bit integers when they fit and as strings otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            Self::Undefined | Self::Hole => serializer.serialize_unit(),
+            Self::Null => serializer.serialize_none(),
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::Number(Number::Integer(value)) => serializer.serialize_i64(*value),
+            Self::Number(Number::Float(value)) => serializer.serialize_f64(*value),
+            Self::BigInt(value) => match i128::try_from(value) {
+                Ok(value) => serializer.serialize_i128(value),
+                Err(_) => serializer.serialize_str(&value.to_string()),
+            },
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Regex(regex) => regex.serialize(serializer),
+            Self::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+
+                seq.end()
+            }
+            Self::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+
+                for (key, value) in object.iter() {
+                    map.serialize_entry(key, value)?;
+                }
+
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_newtype_struct(NAME, ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("any value")
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i128<E: serde::de::Error>(self, v: i128) -> Result<Self::Value, E> {
+        Ok(i64::try_from(v).map_or_else(|_| Value::BigInt(v.into()), Value::from))
+    }
+
+    fn visit_u128<E: serde::de::Error>(self, v: u128) -> Result<Self::Value, E> {
+        Ok(i64::try_from(v).map_or_else(|_| Value::BigInt(v.into()), Value::from))
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut object = Object::new();
+
+        if let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                UNDEFINED_KEY => return map.next_value::<()>().map(|()| Value::Undefined),
+                HOLE_KEY => return map.next_value::<()>().map(|()| Value::Hole),
+                BIG_INT_KEY => {
+                    return map
+                        .next_value::<String>()?
+                        .parse()
+                        .map(Value::BigInt)
+                        .map_err(|_| {
+                            A::Error::invalid_value(serde::de::Unexpected::Map, &"a big integer")
+                        });
+                }
+                REGEX_KEY => return map.next_value().map(Value::Regex),
+                _ => {
+                    object.insert(key, map.next_value()?);
+                }
+            }
+        }
+
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+
+        Ok(Value::Object(object))
+    }
+}
+
+/// A value given to [`Value`]'s visitor by this crate's deserializer, where `undefined`, holes,
+/// big integers, and regular expressions are encoded as maps with a single entry that the visitor
+/// recognizes.
+pub(crate) struct Encoded(pub Value);
+
+impl<'de> Deserializer<'de> for Encoded {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Undefined => {
+                visitor.visit_map(MapDeserializer::new(std::iter::once((UNDEFINED_KEY, ()))))
+            }
+            Value::Hole => visitor.visit_map(MapDeserializer::new(std::iter::once((HOLE_KEY, ())))),
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(value) => visitor.visit_bool(value),
+            Value::Number(Number::Integer(value)) => visitor.visit_i64(value),
+            Value::Number(Number::Float(value)) => visitor.visit_f64(value),
+            Value::BigInt(value) => visitor.visit_map(MapDeserializer::new(std::iter::once((
+                BIG_INT_KEY,
+                value.to_string(),
+            )))),
+            Value::String(value) => visitor.visit_string(value),
+            Value::Regex(regex) => visitor.visit_map(MapDeserializer::new(std::iter::once((
+                REGEX_KEY,
+                regex.to_string(),
+            )))),
+            Value::Array(values) => {
+                visitor.visit_seq(SeqDeserializer::new(values.into_iter().map(Self)))
+            }
+            Value::Object(object) => visitor.visit_map(MapDeserializer::new(
+                object.into_iter().map(|(key, value)| (key, Self(value))),
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, Error> for Encoded {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+impl<'de> serde::de::IntoDeserializer<'de, Error> for Value {
+    type Deserializer = crate::Deserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        crate::Deserializer::new(Cow::Owned(self.to_expr()), Context::default())
+    }
+}
+
+/// The value of an evaluated expression whose kind [`crate::Deserializer`] cannot convey exactly
+/// through `deserialize_any`: `undefined`, `null` (which is seen as absent), numbers (which are
+/// not distinguished as integers or floats), big integers, regular expressions, and templates.
+///
+/// Returns `None` for any other expression, which is deserialized as it is for any other
+/// self-describing type.
+pub(crate) fn literal(expr: &Expr, context: &Context<'_>) -> Option<Result<Value, Error>> {
+    let value = match expr {
+        Expr::Lit(Lit::Null(_)) => Value::Null,
+        Expr::Lit(Lit::Num(number)) => number_value(number),
+        Expr::Lit(Lit::BigInt(big_int)) => Value::BigInt((*big_int.value).clone()),
+        Expr::Lit(Lit::Regex(regex)) => Value::Regex(regex.into()),
+        Expr::Ident(ident) if ident.sym == "undefined" => Value::Undefined,
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) if let Expr::Lit(Lit::BigInt(big_int)) = arg.unwrap_parens() => {
+            Value::BigInt(-(*big_int.value).clone())
+        }
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) if matches!(arg.unwrap_parens(), Expr::Ident(ident) if ident.sym == "Infinity") => {
+            Value::Number(Number::Float(f64::NEG_INFINITY))
+        }
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => {
+            let value = tpl
+                .quasis
+                .iter()
+                .map(|quasi| quasi.cooked.as_ref().map_or("", |cooked| cooked.as_str()))
+                .collect::<String>();
+
+            return Some(
+                context
+                    .consume_string(value.len())
+                    .map(|()| Value::String(value)),
+            );
+        }
+        Expr::New(_) | Expr::Call(_) => {
+            return crate::regex::from_expr(expr).map(|regex| regex.map(Value::Regex));
+        }
+        other => {
+            return crate::number::signed_literal(other).map(|number| Ok(number_value(&number)));
+        }
+    };
+
+    Some(Ok(value))
+}
+
+/// A number written as an integer is an integer unless it is negative zero, which a folded `-0`
+/// is.
+fn number_value(number: &swc_ecma_ast::Number) -> Value {
+    Value::Number(
+        if crate::number::is_integer(number)
+            && !(number.value == 0.0 && number.value.is_sign_negative())
+        {
+            crate::number::number_to_i64(number)
+        } else {
+            None
+        }
+        .map_or(Number::Float(number.value), Number::Integer),
+    )
+}
+
+/// The deserializer for a missing element of an array, which is only accepted by [`Value`].
+pub(crate) struct Hole;
+
+impl<'de> Deserializer<'de> for Hole {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(Error::InvalidArrayElement(None))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if name == NAME {
+            visitor.visit_newtype_struct(Encoded(Value::Hole))
+        } else {
+            Err(Error::InvalidArrayElement(None))
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+fn ident_expr(name: &str) -> Expr {
+    Expr::Ident(Ident::new_no_ctxt(name.into(), DUMMY_SP))
+}
+
+/// Construct a [`Value`] using JavaScript-like syntax.
+///
+/// Object keys may be identifiers or literals. Any other Rust expression is converted with
+/// [`From`].
+///
+/// ```
+/// use ecmade::{js, Value};
+///
+/// let value = js!({ name: "web", ports: [80, 8080], "extra key": undefined, parent: null });
+///
+/// assert_eq!(value["ports"][1], Value::from(8080));
+/// assert!(value["extra key"].is_undefined());
+/// ```
+#[macro_export]
+macro_rules! js {
+    (null) => {
+        $crate::value::Value::Null
+    };
+    (undefined) => {
+        $crate::value::Value::Undefined
+    };
+    (true) => {
+        $crate::value::Value::Bool(true)
+    };
+    (false) => {
+        $crate::value::Value::Bool(false)
+    };
+    ([]) => {
+        $crate::value::Value::Array(::std::vec![])
+    };
+    ([ $($tt:tt)+ ]) => {
+        $crate::value::Value::Array($crate::js!(@array [] $($tt)+))
+    };
+    ({}) => {
+        $crate::value::Value::Object($crate::value::Object::new())
+    };
+    ({ $($tt:tt)+ }) => {
+        $crate::value::Value::Object(
+            ::std::iter::IntoIterator::into_iter($crate::js!(@object [] $($tt)+)).collect(),
+        )
+    };
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        ::std::vec![$($elems),*]
+    };
+    (@array [$($elems:expr,)*] null $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)* $crate::js!(null)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] undefined $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)* $crate::js!(undefined)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] true $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)* $crate::js!(true)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] false $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)* $crate::js!(false)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)* $crate::js!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)* $crate::js!({$($object)*})] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)* $crate::value::Value::from($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::js!(@array [$($elems,)* $crate::value::Value::from($last)])
+    };
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::js!(@array [$($elems,)*] $($rest)*)
+    };
+    (@object [$($entries:expr,)*]) => {
+        ::std::vec![$($entries,)*]
+    };
+    (@object [$($entries:expr),*]) => {
+        ::std::vec![$($entries),*]
+    };
+    (@object [$($entries:expr,)*] $key:tt : null $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::js!(null))] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : undefined $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::js!(undefined))] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : true $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::js!(true))] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : false $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::js!(false))] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : [$($array:tt)*] $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::js!([$($array)*]))] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : {$($object:tt)*} $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::js!({$($object)*}))] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : $value:expr, $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::value::Value::from($value)),] $($rest)*)
+    };
+    (@object [$($entries:expr,)*] $key:tt : $value:expr) => {
+        $crate::js!(@object [$($entries,)* ($crate::js!(@key $key), $crate::value::Value::from($value))])
+    };
+    (@object [$($entries:expr),*] , $($rest:tt)*) => {
+        $crate::js!(@object [$($entries,)*] $($rest)*)
+    };
+    (@key $key:ident) => {
+        ::std::string::String::from(::std::stringify!($key))
+    };
+    (@key $key:literal) => {
+        ::std::string::ToString::to_string(&$key)
+    };
+    ($other:expr) => {
+        $crate::value::Value::from($other)
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::{JsonPolicy, Number, Regex, Value};
+
+    #[test]
+    fn from_str() -> Result<(), crate::error::Error> {
+        let value = crate::from_str::<Value>(
+            "{ data: [1, 1.0, , undefined, -2, NaN, -Infinity, 10n, /a+/g, `x`], 0: null }",
+        )?;
+
+        let expected = js!({
+            data: [
+                1,
+                Value::Number(Number::Float(1.0)),
+                Value::Hole,
+                undefined,
+                -2,
+                f64::NAN,
+                f64::NEG_INFINITY,
+                Value::BigInt(10.into()),
+                Value::Regex(Regex::new("a+", "g")),
+                "x"
+            ],
+            "0": null
+        });
+
+        assert_eq!(value["data"][0], Value::from(1));
+        assert_eq!(value["data"][1], Value::Number(Number::Float(1.0)));
+        assert_eq!(value["data"][2], Value::Hole);
+        assert!(value["data"][5].as_f64().is_some_and(f64::is_nan));
+        assert_eq!(value["data"][8], expected["data"][8]);
+        assert!(value["missing"]["deeply"].is_undefined());
+        assert_eq!(value["0"], Value::Null);
+
+        assert_eq!(
+            value["data"].as_array().map(<[Value]>::len),
+            expected["data"].as_array().map(<[Value]>::len)
+        );
+
+        for options in [
+            crate::Options::new(),
+            crate::Options::new().fold_constants(true),
+        ] {
+            assert!(matches!(
+                options.from_str::<Value>("-0")?,
+                Value::Number(Number::Float(zero)) if zero == 0.0 && zero.is_sign_negative()
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn builtins() -> Result<(), crate::error::Error> {
+        let value = crate::from_str::<Value>(
+            "{
+                map: new Map([['a', 1]]),
+                set: new Set([1, 2]),
+                date: new Date(0),
+                bytes: new Uint8Array([1, 2]),
+                assigned: Object.assign({}, { a: 1 }),
+                frozen: Object.freeze({ a: 1 }),
+            }",
+        )?;
+
+        assert_eq!(
+            value,
+            js!({
+                map: { a: 1 },
+                set: [1, 2],
+                date: "1970-01-01T00:00:00.000Z",
+                bytes: [1, 2],
+                assigned: { a: 1 },
+                frozen: { a: 1 }
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn object() {
+        let mut object = (0..10_000)
+            .map(|index| (index.to_string(), Value::from(index)))
+            .collect::<super::Object>();
+
+        assert_eq!(object.insert("0".to_string(), Value::Null), Some(0.into()));
+        assert_eq!(object.insert("x".to_string(), Value::Null), None);
+        assert_eq!(object.len(), 10_001);
+        assert_eq!(object.get("9999"), Some(&Value::from(9999)));
+        assert_eq!(
+            object
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .take(2)
+                .collect::<Vec<_>>(),
+            ["0", "1"]
+        );
+        assert_eq!(
+            object.into_iter().last(),
+            Some(("x".to_string(), Value::Null))
+        );
+    }
+
+    #[test]
+    fn round_trip() -> Result<(), crate::error::Error> {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Target {
+            a: (u8, f64, String, Option<bool>, Option<bool>),
+        }
+
+        let value = js!({ a: [1, 2.5, "three", null, undefined], "b c": { d: true } });
+        let expr = value.to_expr();
+
+        assert_eq!(crate::from_expr::<Value>(&expr)?, value);
+        assert_eq!(Value::from_expr(&expr)?, value);
+
+        let target: Target = serde::Deserialize::deserialize(serde::de::IntoDeserializer::<
+            crate::error::Error,
+        >::into_deserializer(js!({
+            a: [1, 2.5, "three", null, null]
+        })))?;
+
+        assert_eq!(target.a, (1, 2.5, "three".to_string(), None, None));
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_json() -> Result<(), crate::error::Error> {
+        let value = js!({ a: [1, undefined, f64::INFINITY], b: Value::BigInt(1.into()) });
+
+        assert_eq!(
+            value.to_json(JsonPolicy::Null)?,
+            serde_json::json!({ "a": [1, null, null], "b": 1 })
+        );
+        assert_eq!(
+            value.to_json(JsonPolicy::String)?,
+            serde_json::json!({ "a": [1, "undefined", "Infinity"], "b": 1 })
+        );
+        assert!(value.to_json(JsonPolicy::Error).is_err());
+        assert_eq!(
+            serde_json::to_value(&value).unwrap(),
+            serde_json::json!({ "a": [1, null, null], "b": 1 })
+        );
+
+        Ok(())
+    }
+
+    #[derive(serde::Serialize)]
+    enum Shape {
+        Point,
+        Circle(f64),
+        Rect { width: u8, height: u8 },
+    }
+
+    #[derive(serde::Serialize)]
+    struct Deployment {
+        name: &'static str,
+        replicas: Option<u8>,
+        limits: std::collections::BTreeMap<u16, i128>,
+        shapes: Vec<Shape>,
+        pair: (char, ()),
+    }
+
+    #[test]
+    fn to_value() -> Result<(), crate::error::Error> {
+        let deployment = Deployment {
+            name: "web",
+            replicas: None,
+            limits: [(1, 2), (3, i128::MAX)].into_iter().collect(),
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(0.5),
+                Shape::Rect {
+                    width: 1,
+                    height: 2,
+                },
+            ],
+            pair: ('x', ()),
+        };
+
+        assert_eq!(
+            super::to_value(&deployment)?,
+            js!({
+                name: "web",
+                replicas: null,
+                limits: { "1": 2, "3": Value::BigInt(i128::MAX.into()) },
+                shapes: ["Point", { Circle: 0.5 }, { Rect: { width: 1, height: 2 } }],
+                pair: ["x", undefined],
+            })
+        );
+
+        let value = crate::from_str::<Value>(
+            "{ a: [1, 2.5, null, undefined], b: -100000000000000000000n }",
+        )?;
+
+        assert_eq!(super::to_value(&value)?, value);
+        assert!(
+            super::to_value(&std::iter::once(((), 1)).collect::<std::collections::HashMap<_, _>>())
+                .is_err()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use crate::path::Path;
+use swc_common::Span;
+
+/// A conversion that succeeded but may not have preserved the original value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub path: Path,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WarningKind {
+    /// A number was deserialized as an `f32` that does not represent it exactly.
+    TruncatedFloat(f64),
+    /// An identifier was deserialized as a string containing its name.
+    IdentifierAsString(String),
+    /// A `null` was deserialized as an absent value by a self-describing type.
+    NullAsNone,
+    /// A member or call expression that could not be evaluated was deserialized by a
+    /// self-describing type as the enum variant it names.
+    ExpressionAsVariant(String),
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            WarningKind::TruncatedFloat(value) => {
+                write!(f, "Number {value} truncated to f32 at {}", self.path)
+            }
+            WarningKind::IdentifierAsString(name) => {
+                write!(f, "Identifier {name} used as string at {}", self.path)
+            }
+            WarningKind::NullAsNone => write!(f, "Null used as none at {}", self.path),
+            WarningKind::ExpressionAsVariant(name) => {
+                write!(f, "Expression used as enum variant {name} at {}", self.path)
+            }
+        }
+    }
+}